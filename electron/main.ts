@@ -125,7 +125,9 @@ function registerIpcHandlers() {
 
     ipcMain.handle('add-secret', (_event, key: string, value: string) => {
         if (vaultCore) {
-            return vaultCore.addSecret(key, value);
+            // Throws with the reason on failure, which rejects the renderer's promise
+            vaultCore.addSecret(key, value);
+            return true;
         }
         return false;
     });
@@ -155,9 +157,11 @@ function registerIpcHandlers() {
         return '';
     });
 
-    ipcMain.handle('sync-to-shell', () => {
+    ipcMain.handle('sync-to-shell', (_event, shell?: string) => {
         if (vaultCore) {
-            return vaultCore.syncToShell();
+            // Throws with a `code` on failure, like add-secret
+            vaultCore.syncToShell(shell);
+            return true;
         }
         return false;
     });
@@ -26,7 +26,7 @@ contextBridge.exposeInMainWorld('electronAPI', {
     exportEnv: (): Promise<string> => ipcRenderer.invoke('export-env'),
 
     // Shell sync - exports secrets to ~/.envvault for project usage
-    syncToShell: (): Promise<boolean> => ipcRenderer.invoke('sync-to-shell'),
+    syncToShell: (shell?: string): Promise<boolean> => ipcRenderer.invoke('sync-to-shell', shell),
     getEnvvaultPath: (): Promise<string> => ipcRenderer.invoke('get-envvault-path')
 });
 
@@ -0,0 +1,66 @@
+use crate::error::{ErrorCode, VaultError, VaultResult};
+use crate::{prepare_key, with_conn};
+use napi_derive::napi;
+use rusqlite::{params, Connection, OptionalExtension};
+
+/// Namespace of secret `id`, or `NotFound`
+fn secret_namespace(conn: &Connection, id: u32) -> VaultResult<String> {
+    conn.query_row("SELECT namespace FROM secrets WHERE id = ?1", params![id], |row| row.get(0))
+        .optional()?
+        .ok_or(VaultError::NotFound(id))
+}
+
+/// Give a secret another name that `get_secret_by_key` finds it by and
+/// that .env exports can include, e.g. `DB_URL` for `DATABASE_URL`. An
+/// alias must be a valid key, and fails with `KeyExists` if a key or alias
+/// in the secret's namespace already uses it.
+#[napi]
+pub fn add_alias(id: u32, alias: String) -> napi::Result<(), ErrorCode> {
+    with_conn(|conn| {
+        let alias = prepare_key(conn, &alias)?;
+        let tx = conn.transaction()?;
+        let namespace = secret_namespace(&tx, id)?;
+        let taken = tx
+            .prepare(
+                "SELECT 1 FROM secrets s WHERE s.namespace = ?1 AND (s.key = ?2
+                    OR EXISTS (SELECT 1 FROM secret_aliases a WHERE a.secret_id = s.id AND a.alias = ?2))",
+            )?
+            .exists(params![namespace, alias])?;
+        if taken {
+            return Err(VaultError::KeyExists(alias));
+        }
+        tx.execute("INSERT INTO secret_aliases (secret_id, alias) VALUES (?1, ?2)", params![id, alias])?;
+        tx.commit()?;
+        Ok(())
+    })?;
+
+    Ok(())
+}
+
+/// Remove an alias from a secret, returning whether it had that alias
+#[napi]
+pub fn remove_alias(id: u32, alias: String) -> napi::Result<bool, ErrorCode> {
+    let removed = with_conn(|conn| {
+        secret_namespace(conn, id)?;
+        let removed = conn.execute(
+            "DELETE FROM secret_aliases WHERE secret_id = ?1 AND alias = ?2",
+            params![id, alias],
+        )?;
+        Ok(removed > 0)
+    })?;
+
+    Ok(removed)
+}
+
+/// List a secret's aliases, alphabetically
+#[napi]
+pub fn list_aliases(id: u32) -> napi::Result<Vec<String>, ErrorCode> {
+    let aliases = with_conn(|conn| {
+        secret_namespace(conn, id)?;
+        let mut stmt = conn.prepare("SELECT alias FROM secret_aliases WHERE secret_id = ?1 ORDER BY alias ASC")?;
+        let rows = stmt.query_map(params![id], |row| row.get(0))?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    })?;
+
+    Ok(aliases)
+}
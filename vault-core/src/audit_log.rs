@@ -0,0 +1,123 @@
+use crate::{get_meta, with_conn, MAX_PAGE_SIZE};
+use crate::error::{ErrorCode, VaultResult};
+use napi_derive::napi;
+use rusqlite::{params, Connection};
+
+/// One recorded vault operation, as returned to JavaScript
+#[napi(object)]
+pub struct AuditEntry {
+    pub id: u32,
+    /// `"add"`, `"update"`, `"delete"`, `"clear"`, `"read"`, `"export"` or `"import"`
+    pub operation: String,
+    /// Secret the operation touched, if it was about a single secret
+    pub secret_id: Option<u32>,
+    pub namespace: Option<String>,
+    pub key: Option<String>,
+    /// ISO-8601 UTC timestamp
+    pub at: String,
+    /// Justification given with `reveal_secret_with_reason`
+    pub reason: Option<String>,
+}
+
+/// Whether operations are being recorded; on unless turned off
+fn enabled(conn: &Connection) -> bool {
+    get_meta(conn, "audit_log").as_deref() != Some("0")
+}
+
+/// Record `operation` against secret `id`. Only the id, namespace and key
+/// are stored, never the value; nothing is recorded if the secret doesn't
+/// exist, so deletions must be recorded before the row goes.
+pub(crate) fn record_secret(conn: &Connection, operation: &str, id: u32) -> VaultResult<()> {
+    if enabled(conn) {
+        conn.execute(
+            "INSERT INTO audit_log (operation, secret_id, namespace, key)
+             SELECT ?1, id, namespace, key FROM secrets WHERE id = ?2",
+            params![operation, id],
+        )?;
+    }
+    Ok(())
+}
+
+/// Record a read of secret `id` along with the reason given for it. This
+/// is recorded even while logging is turned off, since the reason is only
+/// asked for so that it can be kept.
+pub(crate) fn record_reason(conn: &Connection, id: u32, reason: &str) -> VaultResult<()> {
+    conn.execute(
+        "INSERT INTO audit_log (operation, secret_id, namespace, key, reason)
+         SELECT 'read', id, namespace, key, ?1 FROM secrets WHERE id = ?2",
+        params![reason, id],
+    )?;
+    Ok(())
+}
+
+/// Record `operation` against secret `key` in `namespace`
+pub(crate) fn record_key(conn: &Connection, operation: &str, namespace: &str, key: &str) -> VaultResult<()> {
+    if enabled(conn) {
+        conn.execute(
+            "INSERT INTO audit_log (operation, secret_id, namespace, key)
+             SELECT ?1, id, namespace, key FROM secrets WHERE namespace = ?2 AND key = ?3",
+            params![operation, namespace, key],
+        )?;
+    }
+    Ok(())
+}
+
+/// Record an operation covering a whole namespace, or every namespace when `None`
+pub(crate) fn record_vault(conn: &Connection, operation: &str, namespace: Option<&str>) -> VaultResult<()> {
+    if enabled(conn) {
+        conn.execute(
+            "INSERT INTO audit_log (operation, namespace) VALUES (?1, ?2)",
+            params![operation, namespace],
+        )?;
+    }
+    Ok(())
+}
+
+/// Get the most recent `limit` audit entries, newest first
+#[napi]
+pub fn get_audit_log(limit: u32) -> napi::Result<Vec<AuditEntry>, ErrorCode> {
+    let entries = with_conn(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT id, operation, secret_id, namespace, key, strftime('%Y-%m-%dT%H:%M:%SZ', at), reason
+             FROM audit_log ORDER BY id DESC LIMIT ?1",
+        )?;
+
+        let rows = stmt.query_map(params![limit.min(MAX_PAGE_SIZE)], |row| {
+            Ok(AuditEntry {
+                id: row.get(0)?,
+                operation: row.get(1)?,
+                secret_id: row.get(2)?,
+                namespace: row.get(3)?,
+                key: row.get(4)?,
+                at: row.get(5)?,
+                reason: row.get(6)?,
+            })
+        })?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    })?;
+
+    Ok(entries)
+}
+
+/// Turn recording of vault operations on or off. Entries already recorded
+/// are kept either way.
+#[napi]
+pub fn set_audit_logging(enabled: bool) -> napi::Result<(), ErrorCode> {
+    with_conn(|conn| {
+        conn.execute(
+            "INSERT OR REPLACE INTO vault_meta (name, value) VALUES ('audit_log', ?1)",
+            params![if enabled { "1" } else { "0" }],
+        )?;
+        Ok(())
+    })?;
+    Ok(())
+}
+
+/// Delete entries recorded more than `days` days ago, returning how many went
+pub(crate) fn prune(conn: &Connection, days: u32) -> VaultResult<u32> {
+    let removed = conn.execute(
+        "DELETE FROM audit_log WHERE at < datetime('now', ?1)",
+        params![format!("-{} days", days)],
+    )?;
+    Ok(removed as u32)
+}
@@ -0,0 +1,214 @@
+use crate::error::{ErrorCode, VaultError, VaultResult};
+use crate::namespace_keys::VaultKeys;
+use crate::tags::{normalize_tag, split_tags};
+use crate::{
+    audit_log, crypto, files, kdf_params, open_value, read_import_file, require_key, upsert_secret, with_conn, ImportReport,
+    ImportStrategy,
+};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use napi_derive::napi;
+use rusqlite::{params, Connection};
+use serde_json::{json, Value};
+use zeroize::{Zeroize, Zeroizing};
+
+/// Marks a file as an EnvVault backup
+const BACKUP_FORMAT: &str = "envvault-backup";
+
+/// Version of the archive layout written by `backup_vault`
+const BACKUP_VERSION: u64 = 1;
+
+/// One secret in the decrypted archive: namespace, key, value,
+/// description, expiry and tags
+type BackupEntry = (String, String, String, Option<String>, Option<String>, Vec<String>);
+
+/// Overwrite the decrypted values once they're no longer needed
+fn wipe_values(entries: &mut [BackupEntry]) {
+    for (_, _, value, ..) in entries {
+        value.zeroize();
+    }
+}
+
+/// Read every secret in every namespace, decrypted, for the archive
+fn collect_entries(conn: &Connection) -> VaultResult<Vec<BackupEntry>> {
+    require_key(conn)?;
+    let keys = VaultKeys::load(conn)?;
+    let mut stmt = conn.prepare(
+        "SELECT namespace, key, value, nonce, description, expires_at,
+            (SELECT group_concat(t.name, char(31)) FROM secret_tags st JOIN tags t ON t.id = st.tag_id
+                WHERE st.secret_id = secrets.id),
+            compressed
+         FROM secrets WHERE NOT binary ORDER BY namespace ASC, key ASC",
+    )?;
+
+    let rows = stmt.query_map([], |row| {
+        let namespace: String = row.get(0)?;
+        let (ciphertext, nonce): (String, String) = (row.get(2)?, row.get(3)?);
+        let compressed: bool = row.get(7)?;
+        let value = keys.require(&namespace).map(|key| open_value(key, &ciphertext, &nonce, compressed));
+        Ok((
+            namespace,
+            row.get::<_, String>(1)?,
+            value,
+            row.get(4)?,
+            row.get(5)?,
+            split_tags(row.get(6)?),
+        ))
+    })?;
+
+    rows.map(|row| {
+        let (namespace, name, value, description, expires_at, tags) = row?;
+        let value = value?.ok_or_else(|| VaultError::Crypto(format!("failed to decrypt {}", name)))?;
+        Ok((namespace, name, value.to_string(), description, expires_at, tags))
+    })
+    .collect()
+}
+
+/// Write every secret, with its description, expiry and tags, to an
+/// archive at `path` encrypted under `password`. The archive has its own
+/// salt and key, so it can be restored on another machine regardless of
+/// that vault's master password. Binary secrets aren't included, nor are
+/// namespace passwords, though the secrets of every namespace are, so any
+/// namespace with its own password must be unlocked first. Fails with
+/// `Locked` while the vault is locked.
+#[napi]
+pub fn backup_vault(path: String, password: String) -> napi::Result<(), ErrorCode> {
+    let password = Zeroizing::new(password);
+    if password.is_empty() {
+        return Err(VaultError::InvalidArgument("backup password must not be empty".to_string()).into());
+    }
+
+    let (mut entries, params) = with_conn(|conn| {
+        let entries = collect_entries(conn)?;
+        audit_log::record_vault(conn, "export", None)?;
+        Ok((entries, kdf_params(conn)))
+    })?;
+    // Sized generously so the buffer holding the values never reallocates
+    let capacity = entries
+        .iter()
+        .map(|(ns, k, v, d, ..)| ns.len() + k.len() + v.len() * 2 + d.as_ref().map_or(0, |d| d.len() * 2) + 96)
+        .sum::<usize>()
+        + 2;
+    let mut plaintext = Zeroizing::new(Vec::with_capacity(capacity));
+    let written = serde_json::to_writer(&mut *plaintext, &entries);
+    wipe_values(&mut entries);
+    written.map_err(|err| VaultError::Parse(err.to_string()))?;
+
+    let crypto_err = || VaultError::Crypto("failed to encrypt backup".to_string());
+    let salt = crypto::random_salt().ok_or_else(crypto_err)?;
+    let key = crypto::derive_key(&password, &salt, params).ok_or_else(crypto_err)?;
+    let (ciphertext, nonce) = crypto::encrypt(&key, &plaintext).ok_or_else(crypto_err)?;
+
+    let archive = json!({
+        "format": BACKUP_FORMAT,
+        "version": BACKUP_VERSION,
+        "kdf_memory_kib": params.memory_kib,
+        "kdf_iterations": params.iterations,
+        "kdf_parallelism": params.parallelism,
+        "salt": BASE64.encode(salt),
+        "nonce": nonce,
+        "ciphertext": ciphertext,
+    });
+    files::write_private_atomic(std::path::Path::new(&path), archive.to_string().as_bytes())
+        .map_err(|err| VaultError::io_at(&path, err))?;
+    Ok(())
+}
+
+/// Decrypt a backup archive written by `backup_vault`
+fn open_archive(content: &str, password: &str) -> VaultResult<Vec<BackupEntry>> {
+    let invalid = || VaultError::Parse("not an EnvVault backup".to_string());
+    let archive: Value = serde_json::from_str(content).map_err(|_| invalid())?;
+    if archive["format"] != BACKUP_FORMAT {
+        return Err(invalid());
+    }
+    if archive["version"] != BACKUP_VERSION {
+        return Err(VaultError::Parse(format!("unsupported backup version {}", archive["version"])));
+    }
+
+    let field = |name: &str| archive[name].as_str().ok_or_else(invalid);
+    let number = |name: &str| {
+        archive[name].as_u64().and_then(|n| u32::try_from(n).ok()).ok_or_else(invalid)
+    };
+    let params = crypto::KdfParams {
+        memory_kib: number("kdf_memory_kib")?,
+        iterations: number("kdf_iterations")?,
+        parallelism: number("kdf_parallelism")?,
+    };
+    let salt = BASE64.decode(field("salt")?).map_err(|_| invalid())?;
+
+    let key = crypto::derive_key(password, &salt, params)
+        .ok_or_else(|| VaultError::Crypto("failed to derive backup key".to_string()))?;
+    let plaintext = crypto::decrypt(&key, field("ciphertext")?, field("nonce")?)
+        .ok_or_else(|| VaultError::Crypto("wrong backup password or corrupted archive".to_string()))?;
+    serde_json::from_slice(&plaintext).map_err(|_| invalid())
+}
+
+/// Restore the secrets from an archive written by `backup_vault` in one
+/// transaction, resolving existing keys with the same strategies as
+/// `import_from_env_string_with_strategy`. Conflicts are reported as
+/// `namespace/key`. Descriptions, expiries and tags are restored with
+/// each secret written. Each secret is encrypted under whatever key its
+/// namespace uses in this vault, so a namespace with its own password here
+/// must be unlocked first.
+#[napi]
+pub fn restore_vault(path: String, password: String, strategy: String) -> napi::Result<ImportReport, ErrorCode> {
+    let password = Zeroizing::new(password);
+    let strategy = ImportStrategy::parse(&strategy)?;
+    let content = Zeroizing::new(read_import_file(&path)?);
+    let mut entries = open_archive(&content, &password)?;
+
+    let report = with_conn(|conn| {
+        require_key(conn)?;
+        let keys = VaultKeys::load(conn)?;
+        let tx = conn.transaction()?;
+        let mut report = ImportReport { imported: 0, skipped: 0, conflicts: vec![] };
+        let mut pending = Vec::with_capacity(entries.len());
+
+        for entry in &entries {
+            let (namespace, key, ..) = entry;
+            let exists = tx
+                .prepare("SELECT 1 FROM secrets WHERE namespace = ?1 AND key = ?2")?
+                .exists(params![namespace, key])?;
+            if exists {
+                report.conflicts.push(format!("{}/{}", namespace, key));
+                if strategy == ImportStrategy::SkipExisting {
+                    report.skipped += 1;
+                    continue;
+                }
+            }
+            pending.push(entry);
+        }
+
+        // Nothing is written when a conflict should stop the restore
+        if strategy == ImportStrategy::FailOnConflict && !report.conflicts.is_empty() {
+            return Ok(report);
+        }
+
+        for (namespace, key, value, description, expires_at, tags) in pending {
+            upsert_secret(&tx, keys.require(namespace)?, namespace, key, value)?;
+            let id: u32 = tx.query_row(
+                "SELECT id FROM secrets WHERE namespace = ?1 AND key = ?2",
+                params![namespace, key],
+                |row| row.get(0),
+            )?;
+            tx.execute(
+                "UPDATE secrets SET description = ?1, expires_at = ?2 WHERE id = ?3",
+                params![description, expires_at, id],
+            )?;
+            for tag in tags.iter().filter_map(|tag| normalize_tag(tag).ok()) {
+                tx.execute("INSERT OR IGNORE INTO tags (name) VALUES (?1)", params![tag])?;
+                tx.execute(
+                    "INSERT OR IGNORE INTO secret_tags (secret_id, tag_id) SELECT ?1, id FROM tags WHERE name = ?2",
+                    params![id, tag],
+                )?;
+            }
+            report.imported += 1;
+        }
+        audit_log::record_vault(&tx, "import", None)?;
+
+        tx.commit()?;
+        Ok(report)
+    });
+    wipe_values(&mut entries);
+
+    Ok(report?)
+}
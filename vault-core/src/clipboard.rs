@@ -0,0 +1,63 @@
+use crate::error::{ErrorCode, VaultError, VaultResult};
+use crate::{decrypt_secret, ensure_reveal_allowed, with_conn};
+use napi_derive::napi;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+use zeroize::Zeroizing;
+
+/// Seconds a copied value stays on the clipboard when no timeout is given
+const DEFAULT_CLEAR_SECS: u32 = 30;
+
+/// Put the decrypted value of secret `id` on the system clipboard and clear
+/// it after `clear_after_secs` seconds (30 when 0). The value never passes
+/// through JavaScript, and the clipboard is left alone if it no longer holds
+/// the secret by then.
+#[napi]
+pub fn copy_to_clipboard(id: u32, clear_after_secs: u32) -> napi::Result<(), ErrorCode> {
+    let value = with_conn(|conn| {
+        ensure_reveal_allowed(conn)?;
+        decrypt_secret(conn, id)
+    })?;
+    let clear_after = Duration::from_secs(u64::from(if clear_after_secs == 0 {
+        DEFAULT_CLEAR_SECS
+    } else {
+        clear_after_secs
+    }));
+
+    // The clipboard handle lives on its own thread for the whole timeout:
+    // on X11 and Wayland the copied text is only served while it exists
+    let (done, copied) = mpsc::channel::<VaultResult<()>>();
+    thread::spawn(move || {
+        let mut clipboard = match arboard::Clipboard::new() {
+            Ok(clipboard) => clipboard,
+            Err(e) => {
+                let _ = done.send(Err(clipboard_error(e)));
+                return;
+            }
+        };
+        if let Err(e) = clipboard.set_text(value.as_str()) {
+            let _ = done.send(Err(clipboard_error(e)));
+            return;
+        }
+        let _ = done.send(Ok(()));
+
+        thread::sleep(clear_after);
+        let unchanged = clipboard
+            .get_text()
+            .map(|current| Zeroizing::new(current).as_str() == value.as_str())
+            .unwrap_or(false);
+        if unchanged {
+            let _ = clipboard.clear();
+        }
+    });
+
+    copied
+        .recv()
+        .unwrap_or_else(|_| Err(VaultError::Clipboard("clipboard thread exited".to_string())))?;
+    Ok(())
+}
+
+fn clipboard_error(err: arboard::Error) -> VaultError {
+    VaultError::Clipboard(err.to_string())
+}
@@ -0,0 +1,155 @@
+//! LZ4 block-format compression for large secret values. Values are
+//! compressed before they are encrypted, since ciphertext doesn't compress.
+//! A block is only decompressed after AES-GCM has authenticated it, so the
+//! decoder never sees bytes the vault didn't write; it still rejects any
+//! malformed block instead of panicking or over-allocating.
+//!
+//! This in-house codec stands in for `zstd` or `flate2`, which aren't
+//! available in this build. Stored blocks are a little-endian `u32` length
+//! followed by one LZ4 block, the layout `lz4_flex::compress_prepend_size`
+//! writes, so that crate can take over without migrating stored values.
+
+use zeroize::Zeroizing;
+
+/// Shortest run worth encoding as a match
+const MIN_MATCH: usize = 4;
+/// The format requires the last bytes of the input to be literals
+const LAST_LITERALS: usize = 5;
+/// No match may start within this many bytes of the end
+const MATCH_START_MARGIN: usize = 12;
+/// Furthest back a match may refer
+const MAX_OFFSET: usize = u16::MAX as usize;
+/// Size of the table remembering where each 4-byte sequence was last seen
+const HASH_BITS: u32 = 14;
+/// Most output one input byte can expand to; a block claiming more than
+/// this is corrupt
+const MAX_EXPANSION: usize = 255;
+
+/// Slot in the table for a 4-byte sequence
+fn hash(sequence: u32) -> usize {
+    (sequence.wrapping_mul(2_654_435_761) >> (32 - HASH_BITS)) as usize
+}
+
+/// The four bytes of `input` starting at `at`
+fn read_u32(input: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes([input[at], input[at + 1], input[at + 2], input[at + 3]])
+}
+
+/// Append a length that didn't fit in its 4-bit token field
+fn write_extra_length(out: &mut Vec<u8>, mut extra: usize) {
+    while extra >= 255 {
+        out.push(255);
+        extra -= 255;
+    }
+    out.push(extra as u8);
+}
+
+/// Append one sequence: literals, then a match given as `(offset, len)`,
+/// or no match at all for the final sequence
+fn write_sequence(out: &mut Vec<u8>, literals: &[u8], matched: Option<(usize, usize)>) {
+    let match_code = matched.map_or(0, |(_, len)| len - MIN_MATCH);
+    out.push(((literals.len().min(15) as u8) << 4) | match_code.min(15) as u8);
+    if literals.len() >= 15 {
+        write_extra_length(out, literals.len() - 15);
+    }
+    out.extend_from_slice(literals);
+
+    if let Some((offset, _)) = matched {
+        out.extend_from_slice(&(offset as u16).to_le_bytes());
+        if match_code >= 15 {
+            write_extra_length(out, match_code - 15);
+        }
+    }
+}
+
+/// Compress `input` as a single LZ4 block preceded by its length as a
+/// little-endian `u32`. Buffers are sized up front so no copy of the
+/// plaintext is left behind by a reallocation.
+pub fn compress(input: &[u8]) -> Zeroizing<Vec<u8>> {
+    let mut out = Zeroizing::new(Vec::with_capacity(4 + input.len() + input.len() / 255 + 16));
+    out.extend_from_slice(&(input.len() as u32).to_le_bytes());
+    let mut last_seen = vec![usize::MAX; 1 << HASH_BITS];
+    let mut anchor = 0;
+    let mut i = 0;
+
+    while i + MATCH_START_MARGIN < input.len() {
+        let slot = &mut last_seen[hash(read_u32(input, i))];
+        let candidate = std::mem::replace(slot, i);
+        if candidate == usize::MAX || i - candidate > MAX_OFFSET || read_u32(input, candidate) != read_u32(input, i) {
+            i += 1;
+            continue;
+        }
+
+        let max_len = input.len() - LAST_LITERALS - i;
+        let mut len = MIN_MATCH;
+        while len < max_len && input[candidate + len] == input[i + len] {
+            len += 1;
+        }
+        write_sequence(&mut out, &input[anchor..i], Some((i - candidate, len)));
+        i += len;
+        anchor = i;
+    }
+
+    write_sequence(&mut out, &input[anchor..], None);
+    out
+}
+
+/// Read a length continued past its 4-bit token field
+fn read_extra_length(input: &[u8], pos: &mut usize) -> Option<usize> {
+    let mut extra = 0;
+    loop {
+        let byte = *input.get(*pos)?;
+        *pos += 1;
+        extra += byte as usize;
+        if byte != 255 {
+            return Some(extra);
+        }
+    }
+}
+
+/// Decompress a block written by `compress`, or `None` if it's malformed
+pub fn decompress(input: &[u8]) -> Option<Zeroizing<Vec<u8>>> {
+    let expected = u32::from_le_bytes(input.get(..4)?.try_into().ok()?) as usize;
+    if expected > input.len().saturating_mul(MAX_EXPANSION) {
+        return None;
+    }
+    let mut out = Zeroizing::new(Vec::with_capacity(expected));
+    let mut pos = 4;
+
+    loop {
+        let token = *input.get(pos)?;
+        pos += 1;
+
+        let mut literals = (token >> 4) as usize;
+        if literals == 15 {
+            literals += read_extra_length(input, &mut pos)?;
+        }
+        if out.len() + literals > expected {
+            return None;
+        }
+        out.extend_from_slice(input.get(pos..pos.checked_add(literals)?)?);
+        pos += literals;
+        if pos == input.len() {
+            return (out.len() == expected).then_some(out);
+        }
+
+        let offset = u16::from_le_bytes([*input.get(pos)?, *input.get(pos + 1)?]) as usize;
+        pos += 2;
+        if offset == 0 || offset > out.len() {
+            return None;
+        }
+        let mut len = (token & 0x0f) as usize;
+        if len == 15 {
+            len += read_extra_length(input, &mut pos)?;
+        }
+        if out.len() + len + MIN_MATCH > expected {
+            return None;
+        }
+        // Copied a byte at a time, since a match may overlap its own output
+        let start = out.len() - offset;
+        for k in 0..len + MIN_MATCH {
+            let byte = out[start + k];
+            out.push(byte);
+        }
+    }
+}
@@ -0,0 +1,136 @@
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use ring::rand::{SecureRandom, SystemRandom};
+use zeroize::Zeroizing;
+
+/// Length of an AES-256 key in bytes
+pub const KEY_LEN: usize = 32;
+
+/// An AES-256 key, wiped from memory when dropped
+pub type SecretKey = Zeroizing<[u8; KEY_LEN]>;
+
+/// Length of the random Argon2 salt in bytes
+pub const SALT_LEN: usize = 16;
+
+/// Argon2id cost parameters, persisted alongside the salt
+#[derive(Clone, Copy)]
+pub struct KdfParams {
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+}
+
+impl Default for KdfParams {
+    fn default() -> Self {
+        // OWASP baseline recommendation for Argon2id
+        Self {
+            memory_kib: 19 * 1024,
+            iterations: 2,
+            parallelism: 1,
+        }
+    }
+}
+
+/// Derive a 256-bit key from a password using Argon2id
+pub fn derive_key(password: &str, salt: &[u8], params: KdfParams) -> Option<SecretKey> {
+    let params = Params::new(
+        params.memory_kib,
+        params.iterations,
+        params.parallelism,
+        Some(KEY_LEN),
+    )
+    .ok()?;
+
+    let mut key = Zeroizing::new([0u8; KEY_LEN]);
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(password.as_bytes(), salt, &mut *key)
+        .ok()?;
+    Some(key)
+}
+
+/// Generate a fresh random salt for key derivation
+pub fn random_salt() -> Option<[u8; SALT_LEN]> {
+    let mut salt = [0u8; SALT_LEN];
+    SystemRandom::new().fill(&mut salt).ok()?;
+    Some(salt)
+}
+
+/// Generate a fresh random 256-bit key
+pub fn random_key() -> Option<SecretKey> {
+    let mut key = Zeroizing::new([0u8; KEY_LEN]);
+    SystemRandom::new().fill(&mut *key).ok()?;
+    Some(key)
+}
+
+/// Build a string of `length` characters drawn uniformly from `alphabet`.
+/// Random bytes that would bias the modulo are rejected and redrawn.
+pub fn random_string(alphabet: &[u8], length: usize) -> Option<String> {
+    if alphabet.is_empty() || alphabet.len() > 256 {
+        return None;
+    }
+
+    // Largest multiple of the alphabet size that fits in a byte
+    let limit = 256 - 256 % alphabet.len();
+    let rng = SystemRandom::new();
+    let mut out = String::with_capacity(length);
+    let mut buf = [0u8; 64];
+
+    while out.len() < length {
+        rng.fill(&mut buf).ok()?;
+        for &byte in buf.iter().filter(|&&b| (b as usize) < limit) {
+            if out.len() == length {
+                break;
+            }
+            out.push(alphabet[byte as usize % alphabet.len()] as char);
+        }
+    }
+    Some(out)
+}
+
+/// Encrypt a value with AES-256-GCM under a random 96-bit nonce.
+/// Returns `(ciphertext, nonce)`, both base64 encoded for storage.
+pub fn encrypt(key: &[u8; KEY_LEN], plaintext: &[u8]) -> Option<(String, String)> {
+    let mut nonce_bytes = [0u8; NONCE_LEN];
+    SystemRandom::new().fill(&mut nonce_bytes).ok()?;
+
+    let sealing_key = LessSafeKey::new(UnboundKey::new(&AES_256_GCM, key).ok()?);
+    // Holds the plaintext until sealing overwrites it; room for the tag
+    // up front means the buffer never reallocates and leaves a copy behind
+    let mut in_out = Zeroizing::new(Vec::with_capacity(plaintext.len() + AES_256_GCM.tag_len()));
+    in_out.extend_from_slice(plaintext);
+    sealing_key
+        .seal_in_place_append_tag(
+            Nonce::assume_unique_for_key(nonce_bytes),
+            Aad::empty(),
+            &mut *in_out,
+        )
+        .ok()?;
+
+    Some((BASE64.encode(&*in_out), BASE64.encode(nonce_bytes)))
+}
+
+/// Length of the plaintext sealed in a base64 ciphertext from `encrypt`,
+/// worked out without decrypting it
+pub fn plaintext_len(ciphertext: &str) -> Option<usize> {
+    BASE64.decode(ciphertext).ok()?.len().checked_sub(AES_256_GCM.tag_len())
+}
+
+/// Decrypt a base64 `(ciphertext, nonce)` pair produced by `encrypt`.
+/// Returns `None` if the data was tampered with or the key is wrong.
+/// The plaintext is wiped from memory when dropped.
+pub fn decrypt(key: &[u8; KEY_LEN], ciphertext: &str, nonce: &str) -> Option<Zeroizing<Vec<u8>>> {
+    let nonce_bytes: [u8; NONCE_LEN] = BASE64.decode(nonce).ok()?.try_into().ok()?;
+    let mut in_out = Zeroizing::new(BASE64.decode(ciphertext).ok()?);
+
+    let opening_key = LessSafeKey::new(UnboundKey::new(&AES_256_GCM, key).ok()?);
+    let plaintext = opening_key
+        .open_in_place(
+            Nonce::assume_unique_for_key(nonce_bytes),
+            Aad::empty(),
+            &mut in_out,
+        )
+        .ok()?;
+
+    Some(Zeroizing::new(plaintext.to_vec()))
+}
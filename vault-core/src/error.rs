@@ -0,0 +1,169 @@
+use std::fmt;
+
+/// Errors surfaced to JavaScript. Messages name keys and ids but never
+/// include secret values.
+#[derive(Debug)]
+pub enum VaultError {
+    /// The database has no schema yet; `init_database` hasn't been called
+    NotInitialized,
+    /// A master password is set and the vault has not been unlocked
+    Locked,
+    /// The namespace has its own password and hasn't been unlocked
+    NamespaceLocked(String),
+    /// Too many wrong passwords; unlocking is refused for this many more
+    /// seconds
+    LockedOut(u64),
+    /// No secret exists with the given id
+    NotFound(u32),
+    /// No secret exists with the given key
+    KeyNotFound(String),
+    /// The secret is marked read-only and the change wasn't forced
+    ReadOnly(u32),
+    /// A secret with this key already exists in the namespace
+    KeyExists(String),
+    /// The key isn't a valid environment variable name
+    InvalidKey(String),
+    /// An argument had a value the function doesn't accept
+    InvalidArgument(String),
+    /// A value of this many bytes is over the limit of the second
+    TooLarge(usize, usize),
+    /// Imported content couldn't be parsed
+    Parse(String),
+    /// Encrypting or decrypting a value failed
+    Crypto(String),
+    /// Reading or writing a file failed
+    Io(std::io::Error),
+    /// The directory holding the database couldn't be created
+    CreateDir(String, std::io::Error),
+    /// The database file couldn't be opened
+    Open(String, rusqlite::Error),
+    /// Schema migration to the given version failed
+    Migration(u32, Box<VaultError>),
+    /// The system clipboard couldn't be used
+    Clipboard(String),
+    /// The OS keychain couldn't be used
+    Keychain(String),
+    /// The database rejected the operation
+    Db(rusqlite::Error),
+}
+
+pub type VaultResult<T> = Result<T, VaultError>;
+
+/// Stable identifier JavaScript gets as an error's `code`, one of the
+/// strings `VaultError::code` returns
+pub type ErrorCode = &'static str;
+
+impl fmt::Display for VaultError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VaultError::NotInitialized => write!(f, "vault is not initialized; call init_database first"),
+            VaultError::Locked => write!(f, "vault is locked"),
+            VaultError::NamespaceLocked(namespace) => write!(f, "namespace {} is locked", namespace),
+            VaultError::LockedOut(seconds) => {
+                write!(f, "too many failed unlock attempts; try again in {} seconds", seconds)
+            }
+            VaultError::NotFound(id) => write!(f, "secret {} not found", id),
+            VaultError::KeyNotFound(key) => write!(f, "secret {} not found", key),
+            VaultError::ReadOnly(id) => write!(f, "secret {} is read-only", id),
+            VaultError::KeyExists(key) => write!(f, "unique constraint violated on key {}", key),
+            VaultError::InvalidKey(key) => write!(
+                f,
+                "key {:?} must start with a letter or underscore and contain only letters, digits and underscores",
+                key
+            ),
+            VaultError::InvalidArgument(reason) => write!(f, "invalid argument: {}", reason),
+            VaultError::TooLarge(size, limit) => {
+                write!(f, "value is {} bytes, more than the {} bytes allowed", size, limit)
+            }
+            VaultError::Parse(reason) => write!(f, "invalid import: {}", reason),
+            VaultError::Crypto(reason) => write!(f, "encryption error: {}", reason),
+            VaultError::Io(err) => write!(f, "file error: {}", err),
+            VaultError::CreateDir(path, err) => write!(f, "cannot create data directory {}: {}", path, err),
+            VaultError::Open(path, err) => write!(f, "cannot open database {}: {}", path, err),
+            VaultError::Migration(version, err) => write!(f, "schema migration {} failed: {}", version, err),
+            VaultError::Clipboard(reason) => write!(f, "clipboard error: {}", reason),
+            VaultError::Keychain(reason) => write!(f, "keychain error: {}", reason),
+            VaultError::Db(rusqlite::Error::SqliteFailure(err, _))
+                if matches!(err.code, rusqlite::ErrorCode::DatabaseBusy | rusqlite::ErrorCode::DatabaseLocked) =>
+            {
+                write!(f, "database locked")
+            }
+            VaultError::Db(err) => write!(f, "database error: {}", err),
+        }
+    }
+}
+
+impl VaultError {
+    /// Code JavaScript can switch on. These never change once released,
+    /// unlike the messages, which are for people.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            VaultError::NotInitialized => "NOT_INITIALIZED",
+            VaultError::Locked => "LOCKED",
+            VaultError::NamespaceLocked(_) => "NAMESPACE_LOCKED",
+            VaultError::LockedOut(_) => "LOCKED_OUT",
+            VaultError::NotFound(_) | VaultError::KeyNotFound(_) => "NOT_FOUND",
+            VaultError::ReadOnly(_) => "READ_ONLY",
+            VaultError::KeyExists(_) => "KEY_EXISTS",
+            VaultError::InvalidKey(_) => "INVALID_KEY",
+            VaultError::InvalidArgument(_) => "INVALID_ARGUMENT",
+            VaultError::TooLarge(..) => "TOO_LARGE",
+            VaultError::Parse(_) => "PARSE",
+            VaultError::Crypto(_) => "CRYPTO",
+            VaultError::Io(_) | VaultError::CreateDir(..) => "IO",
+            VaultError::Migration(..) => "MIGRATION",
+            VaultError::Clipboard(_) => "CLIPBOARD",
+            VaultError::Keychain(_) => "KEYCHAIN",
+            VaultError::Open(..) | VaultError::Db(_) => "DB",
+        }
+    }
+
+    /// Wrap an I/O error so its message names the file involved
+    pub fn io_at(path: &str, err: std::io::Error) -> Self {
+        VaultError::Io(std::io::Error::new(err.kind(), format!("{}: {}", path, err)))
+    }
+
+    /// Map a unique-constraint failure while writing `key` to `KeyExists`
+    pub fn from_write(err: rusqlite::Error, key: &str) -> Self {
+        match err {
+            rusqlite::Error::SqliteFailure(ref failure, _)
+                if failure.code == rusqlite::ErrorCode::ConstraintViolation =>
+            {
+                VaultError::KeyExists(key.to_string())
+            }
+            err => VaultError::Db(err),
+        }
+    }
+}
+
+impl std::error::Error for VaultError {}
+
+impl From<rusqlite::Error> for VaultError {
+    fn from(err: rusqlite::Error) -> Self {
+        match err {
+            // Any query against a vault `init_database` hasn't set up yet
+            rusqlite::Error::SqliteFailure(_, Some(ref message)) if message.starts_with("no such table") => {
+                VaultError::NotInitialized
+            }
+            err => VaultError::Db(err),
+        }
+    }
+}
+
+impl From<std::io::Error> for VaultError {
+    fn from(err: std::io::Error) -> Self {
+        VaultError::Io(err)
+    }
+}
+
+impl From<VaultError> for napi::Error<ErrorCode> {
+    fn from(err: VaultError) -> Self {
+        napi::Error::new(err.code(), err.to_string())
+    }
+}
+
+/// Hand a coded error back from `Task::resolve`, whose signature only
+/// allows napi's own statuses, as the JS error object so it keeps its code
+pub fn task_error(env: napi::Env, err: napi::Error<ErrorCode>) -> napi::Error {
+    napi::Error::from(napi::JsError::from(err).into_unknown(env))
+}
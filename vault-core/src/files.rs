@@ -0,0 +1,56 @@
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+
+/// Write `contents` to `path` atomically: the data goes to a temporary file
+/// in the same directory which is then renamed over the target, so a crash
+/// never leaves a truncated file. On Unix the file is readable only by the
+/// owner.
+pub fn write_private_atomic(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    write_private_atomic_with(path, |file| file.write_all(contents))
+}
+
+/// Like `write_private_atomic`, but `write` streams the contents into the
+/// temporary file itself. If it fails the target is left untouched.
+pub fn write_private_atomic_with<T, E: From<std::io::Error>>(
+    path: &Path,
+    write: impl FnOnce(&mut File) -> Result<T, E>,
+) -> Result<T, E> {
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::InvalidInput, "path has no file name"))?;
+    let tmp_path = path.with_file_name(format!(
+        ".{}.tmp-{}",
+        file_name.to_string_lossy(),
+        std::process::id()
+    ));
+
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    // The file is closed before the rename, which Windows requires
+    let result = options
+        .open(&tmp_path)
+        .map_err(E::from)
+        .and_then(|mut file| {
+            let written = write(&mut file)?;
+            file.sync_all()?;
+            Ok(written)
+        })
+        .and_then(|written| {
+            std::fs::rename(&tmp_path, path)?;
+            Ok(written)
+        });
+    match result {
+        Ok(written) => Ok(written),
+        Err(err) => {
+            std::fs::remove_file(&tmp_path).ok();
+            Err(err)
+        }
+    }
+}
@@ -0,0 +1,684 @@
+use crate::error::{VaultError, VaultResult};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use zeroize::Zeroizing;
+
+/// A secret read from import content, with the comment that sat directly
+/// above it and the section header it falls under in a `.env` file
+pub struct EnvEntry {
+    pub key: String,
+    pub value: String,
+    pub comment: Option<String>,
+    pub group: Option<String>,
+}
+
+impl From<(String, String)> for EnvEntry {
+    fn from((key, value): (String, String)) -> Self {
+        EnvEntry { key, value, comment: None, group: None }
+    }
+}
+
+/// Parse a flat JSON object of `{"KEY": "value"}` pairs.
+/// Numbers and booleans are stored as their text form; nested objects,
+/// arrays and nulls are rejected.
+pub fn parse_json_object(content: &str) -> VaultResult<Vec<EnvEntry>> {
+    let parsed: Value = serde_json::from_str(content)
+        .map_err(|err| VaultError::Parse(format!("invalid JSON: {}", err)))?;
+
+    let Value::Object(object) = parsed else {
+        return Err(VaultError::Parse("expected a JSON object of KEY: value pairs".to_string()));
+    };
+
+    object
+        .into_iter()
+        .map(|(key, value)| match value {
+            Value::String(value) => Ok((key, value).into()),
+            Value::Number(value) => Ok((key, value.to_string()).into()),
+            Value::Bool(value) => Ok((key, value.to_string()).into()),
+            _ => Err(VaultError::Parse(format!("value for {} must be a string, not nested JSON", key))),
+        })
+        .collect()
+}
+
+/// Render `(key, value)` pairs as a pretty-printed JSON object with sorted keys
+pub fn to_json_object(pairs: &[(String, Zeroizing<String>)]) -> Zeroizing<String> {
+    let object: BTreeMap<&str, &str> = pairs.iter().map(|(key, value)| (key.as_str(), value.as_str())).collect();
+
+    // Sized generously so the buffer holding the values never reallocates
+    let capacity = pairs.iter().map(|(k, v)| k.len() + v.len() * 2 + 16).sum::<usize>() + 4;
+    let mut out = Zeroizing::new(Vec::with_capacity(capacity));
+    if serde_json::to_writer_pretty(&mut *out, &object).is_err() {
+        return Zeroizing::new(String::new());
+    }
+    Zeroizing::new(String::from_utf8(std::mem::take(&mut *out)).unwrap_or_default())
+}
+
+/// Render `(key, value)` pairs as `.env` lines of `KEY="value"`, escaping
+/// exactly what `parse_env` unescapes so any value survives a round trip.
+/// A key with an entry in `comments` gets it as `#` lines above it, set
+/// off from the previous key by a blank line. The output depends only on
+/// its input: pairs are written in the order given, every line ends in
+/// `\n` and no line has trailing whitespace, so exports of the same
+/// secrets are byte-identical on every machine.
+pub fn to_env(pairs: &[(String, Zeroizing<String>)], comments: &HashMap<String, String>) -> Zeroizing<String> {
+    to_env_grouped(pairs, comments, &HashMap::new())
+}
+
+/// Render pairs like `to_env`, starting a `# --- Group ---` section header
+/// wherever the group in `groups` changes from the previous key. Keys of a
+/// group are expected to be next to each other.
+pub fn to_env_grouped(
+    pairs: &[(String, Zeroizing<String>)],
+    comments: &HashMap<String, String>,
+    groups: &HashMap<String, String>,
+) -> Zeroizing<String> {
+    // Sized up front so the buffer never reallocates and leaves copies behind
+    let capacity = pairs
+        .iter()
+        .map(|(k, v)| {
+            k.len()
+                + v.len() * 2
+                + 4
+                + comments.get(k).map_or(0, |c| c.len() * 2 + 4)
+                + groups.get(k).map_or(0, |g| g.len() + 12)
+        })
+        .sum();
+    let mut out = Zeroizing::new(String::with_capacity(capacity));
+    let mut current_group = None;
+
+    for (i, (key, value)) in pairs.iter().enumerate() {
+        let group = groups.get(key);
+        let new_group = group.is_some() && group != current_group;
+        current_group = group;
+        if let (true, Some(group)) = (new_group, group) {
+            if i > 0 {
+                out.push('\n');
+            }
+            out.push_str("# --- ");
+            out.push_str(group);
+            out.push_str(" ---\n");
+        }
+        if let Some(comment) = comments.get(key) {
+            if i > 0 && !new_group {
+                out.push('\n');
+            }
+            for line in comment.lines().map(str::trim_end) {
+                out.push('#');
+                if !line.is_empty() {
+                    out.push(' ');
+                    out.push_str(line);
+                }
+                out.push('\n');
+            }
+        }
+        out.push_str(key);
+        out.push_str("=\"");
+        for c in value.chars() {
+            match c {
+                '"' => out.push_str("\\\""),
+                '\\' => out.push_str("\\\\"),
+                '\n' => out.push_str("\\n"),
+                '\r' => out.push_str("\\r"),
+                '\t' => out.push_str("\\t"),
+                c => out.push(c),
+            }
+        }
+        out.push_str("\"\n");
+    }
+
+    out
+}
+
+/// Render pairs in Docker's `--env-file` format. Docker takes everything
+/// after the first `=` literally, with no quoting or escapes, so values are
+/// written as-is and a value containing a newline can't be represented.
+pub fn to_docker_env(pairs: &[(String, Zeroizing<String>)]) -> VaultResult<Zeroizing<String>> {
+    let capacity = pairs.iter().map(|(k, v)| k.len() + v.len() + 2).sum();
+    let mut out = Zeroizing::new(String::with_capacity(capacity));
+
+    for (key, value) in pairs {
+        if value.contains(['\n', '\r']) {
+            return Err(VaultError::InvalidArgument(format!(
+                "value of {} contains a line break, which Docker env files can't hold",
+                key
+            )));
+        }
+        out.push_str(key);
+        out.push('=');
+        out.push_str(value);
+        out.push('\n');
+    }
+
+    Ok(out)
+}
+
+/// Render pairs as a Kubernetes `Secret` manifest named `name`. Values go
+/// in `data` base64 encoded, so they need no YAML escaping; the name must be
+/// a DNS subdomain and keys may only use letters, digits, `-`, `_` and `.`.
+pub fn to_k8s_secret(name: &str, pairs: &[(String, Zeroizing<String>)]) -> VaultResult<Zeroizing<String>> {
+    let valid_name = !name.is_empty()
+        && name.len() <= 253
+        && name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '.')
+        && name.starts_with(|c: char| c.is_ascii_alphanumeric())
+        && name.ends_with(|c: char| c.is_ascii_alphanumeric());
+    if !valid_name {
+        return Err(VaultError::InvalidArgument(format!("{:?} is not a valid Kubernetes resource name", name)));
+    }
+
+    // `data:` with nothing under it would parse as null
+    let data = if pairs.is_empty() { "data: {}" } else { "data:" };
+    let mut out = Zeroizing::new(format!(
+        "apiVersion: v1\nkind: Secret\nmetadata:\n  name: {}\ntype: Opaque\n{}\n",
+        name, data
+    ));
+    for (key, value) in pairs {
+        if !key.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')) {
+            return Err(VaultError::InvalidArgument(format!("{:?} is not a valid Kubernetes secret key", key)));
+        }
+        let encoded = Zeroizing::new(BASE64.encode(value.as_bytes()));
+        out.push_str("  ");
+        out.push_str(key);
+        out.push_str(": ");
+        out.push_str(&encoded);
+        out.push('\n');
+    }
+
+    Ok(out)
+}
+
+/// Render pairs in systemd `EnvironmentFile=` syntax. Values are double
+/// quoted, where systemd unescapes `\"`, `\\`, `` \` `` and `\$`; line
+/// breaks are allowed inside the quotes and are written literally.
+pub fn to_systemd_env(pairs: &[(String, Zeroizing<String>)]) -> Zeroizing<String> {
+    let capacity = pairs.iter().map(|(k, v)| k.len() + v.len() * 2 + 4).sum();
+    let mut out = Zeroizing::new(String::with_capacity(capacity));
+
+    for (key, value) in pairs {
+        out.push_str(key);
+        out.push_str("=\"");
+        for c in value.chars() {
+            if matches!(c, '"' | '\\' | '`' | '$') {
+                out.push('\\');
+            }
+            out.push(c);
+        }
+        out.push_str("\"\n");
+    }
+
+    out
+}
+
+/// Check `key` against GitHub Actions secret naming rules: ASCII letters,
+/// digits and underscores, not starting with a digit or the reserved
+/// `GITHUB_` prefix. GitHub stores names uppercased, so the name returned is
+/// always uppercase; if the key breaks a rule it is a suggested replacement,
+/// returned with the reason.
+pub fn github_secret_name(key: &str) -> (String, Option<&'static str>) {
+    let upper = key.to_ascii_uppercase();
+    let reason = if upper.is_empty() {
+        Some("name is empty")
+    } else if !upper.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'_') {
+        Some("only letters, digits and underscores are allowed")
+    } else if upper.starts_with(|c: char| c.is_ascii_digit()) {
+        Some("name must not start with a digit")
+    } else if upper.starts_with("GITHUB_") {
+        Some("the GITHUB_ prefix is reserved")
+    } else {
+        None
+    };
+
+    match reason {
+        None => (upper, None),
+        Some(reason) => {
+            let cleaned: String = upper
+                .chars()
+                .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+                .collect();
+            (format!("SECRET_{}", cleaned.trim_matches('_')), Some(reason))
+        }
+    }
+}
+
+/// Parse `.env` content into entries with dotenv semantics.
+///
+/// Double-quoted values may span several lines and unescape `\n`, `\r`,
+/// `\t`, `\"` and `\\`. Single-quoted values are taken literally and may also
+/// span lines. Unquoted values run to the end of the line, minus any
+/// trailing ` #` comment. Everything after the first `=` belongs to the value.
+/// Comment lines directly above a key, with no blank line between, become
+/// that entry's comment. A section header such as `# --- Database ---` or
+/// `# === Database ===` puts the keys below it, up to the next header, in
+/// that group.
+pub fn parse_env(content: &str) -> Vec<EnvEntry> {
+    let mut entries = Vec::new();
+    let mut comment: Vec<&str> = Vec::new();
+    let mut group: Option<&str> = None;
+    let mut pos = 0;
+
+    while pos < content.len() {
+        let line_end = content[pos..].find('\n').map_or(content.len(), |i| pos + i);
+        let line = &content[pos..line_end];
+        let mut next = line_end + 1;
+
+        let trimmed = line.trim();
+        if let Some(header) = trimmed.strip_prefix('#').and_then(section_header) {
+            // A bare divider line is decoration and leaves the group as it is
+            if !header.is_empty() {
+                group = Some(header);
+            }
+            comment.clear();
+        } else if let Some(text) = trimmed.strip_prefix('#') {
+            comment.push(text.strip_prefix(' ').unwrap_or(text));
+        } else if let (false, Some(eq)) = (trimmed.is_empty(), line.find('=')) {
+            let key = line[..eq].trim();
+            let key = key.strip_prefix("export ").map_or(key, str::trim_start);
+
+            let raw = &line[eq + 1..];
+            let value_start = pos + eq + 1 + (raw.len() - raw.trim_start().len());
+
+            // Quoted values may close on a later line
+            let quoted = match content[value_start..].chars().next() {
+                Some('"') => parse_double_quoted(&content[value_start + 1..]),
+                Some('\'') => content[value_start + 1..]
+                    .find('\'')
+                    .map(|end| (content[value_start + 1..value_start + 1 + end].to_string(), end + 1)),
+                _ => None,
+            };
+
+            let value = match quoted {
+                Some((value, consumed)) => {
+                    let close = value_start + 1 + consumed;
+                    next = content[close..].find('\n').map_or(content.len(), |i| close + i + 1);
+                    value
+                }
+                None => strip_inline_comment(raw).trim().to_string(),
+            };
+
+            if !key.is_empty() {
+                entries.push(EnvEntry {
+                    key: key.to_string(),
+                    value,
+                    comment: (!comment.is_empty()).then(|| comment.join("\n")),
+                    group: group.map(str::to_string),
+                });
+            }
+            comment.clear();
+        } else {
+            comment.clear();
+        }
+
+        pos = next;
+    }
+
+    entries
+}
+
+/// The name in a section header comment (the text after `#`), shaped like
+/// `--- Name ---` or `=== Name ===`: a run of at least three `-` or `=`
+/// on both sides. Returns `None` if the comment isn't a header, so a
+/// description such as `--- legacy, remove` stays a comment, and an empty
+/// name for a bare divider line.
+fn section_header(text: &str) -> Option<&str> {
+    let text = text.trim();
+    let marker = ['-', '='].into_iter().find(|&marker| text.starts_with(&marker.to_string().repeat(3)))?;
+    let inner = text.trim_matches(marker);
+    let leading = text.len() - text.trim_start_matches(marker).len();
+    let trailing = text.len() - text.trim_end_matches(marker).len();
+    if !inner.is_empty() && (leading < 3 || trailing < 3 || inner.trim().is_empty()) {
+        return None;
+    }
+    Some(inner.trim())
+}
+
+/// Read a double-quoted value up to its closing quote, unescaping as it goes.
+/// Returns the value and the number of bytes consumed including the quote,
+/// or `None` if the quote is never closed.
+fn parse_double_quoted(s: &str) -> Option<(String, usize)> {
+    let mut value = String::new();
+    let mut chars = s.char_indices();
+
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return Some((value, i + 1)),
+            '\\' => match chars.next() {
+                Some((_, 'n')) => value.push('\n'),
+                Some((_, 'r')) => value.push('\r'),
+                Some((_, 't')) => value.push('\t'),
+                Some((_, '"')) => value.push('"'),
+                Some((_, '\\')) => value.push('\\'),
+                Some((_, other)) => {
+                    value.push('\\');
+                    value.push(other);
+                }
+                None => return None,
+            },
+            c => value.push(c),
+        }
+    }
+
+    None
+}
+
+/// Drop a trailing ` # comment` from an unquoted value
+fn strip_inline_comment(value: &str) -> &str {
+    match value.find(" #") {
+        Some(i) => &value[..i],
+        None => value,
+    }
+}
+
+/// Parse a TOML document whose top level holds only `KEY = value` pairs.
+/// Strings are taken as they are; integers, floats, booleans and dates are
+/// stored as written. Tables, dotted keys and arrays are rejected rather
+/// than flattened, since a vault key can't hold their structure.
+pub fn parse_toml(content: &str) -> VaultResult<Vec<EnvEntry>> {
+    let document: toml_edit::DocumentMut = content
+        .parse()
+        .map_err(|err: toml_edit::TomlError| VaultError::Parse(format!("invalid TOML: {}", err.message())))?;
+
+    document
+        .iter()
+        .map(|(key, item)| {
+            let value = match item {
+                toml_edit::Item::Value(toml_edit::Value::String(value)) => value.value().clone(),
+                toml_edit::Item::Value(value) => toml_scalar_text(value).ok_or_else(|| {
+                    VaultError::Parse(format!("value for {} must be a string, not a nested table or array", key))
+                })?,
+                _ => return Err(VaultError::Parse(format!("value for {} must be a string, not a nested table or array", key))),
+            };
+            Ok((key.to_string(), value).into())
+        })
+        .collect()
+}
+
+/// A number, boolean or date exactly as written in the document; `None`
+/// for arrays and inline tables
+fn toml_scalar_text(value: &toml_edit::Value) -> Option<String> {
+    let repr = match value {
+        toml_edit::Value::Integer(v) => v.as_repr(),
+        toml_edit::Value::Float(v) => v.as_repr(),
+        toml_edit::Value::Boolean(v) => v.as_repr(),
+        toml_edit::Value::Datetime(v) => v.as_repr(),
+        _ => return None,
+    };
+    Some(repr?.as_raw().as_str()?.trim().to_string())
+}
+
+/// Append `value` as a double-quoted string, escaping what TOML basic
+/// strings and YAML double-quoted scalars both require
+fn push_double_quoted(out: &mut String, value: &str) {
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => out.push_str(&format!("\\u{:04X}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/// Render pairs as flat TOML, `KEY = "value"`, one per line
+pub fn to_toml(pairs: &[(String, Zeroizing<String>)]) -> Zeroizing<String> {
+    render_quoted_pairs(pairs, " = ")
+}
+
+/// Render pairs as a flat YAML mapping, `KEY: "value"`, one per line
+pub fn to_yaml(pairs: &[(String, Zeroizing<String>)]) -> Zeroizing<String> {
+    if pairs.is_empty() {
+        return Zeroizing::new("{}\n".to_string());
+    }
+    render_quoted_pairs(pairs, ": ")
+}
+
+/// Write each pair as its key, `separator` and the double-quoted value
+fn render_quoted_pairs(pairs: &[(String, Zeroizing<String>)], separator: &str) -> Zeroizing<String> {
+    // Sized so the buffer never reallocates; an escape is at most six bytes
+    let capacity = pairs.iter().map(|(k, v)| k.len() + v.len() * 6 + separator.len() + 3).sum();
+    let mut out = Zeroizing::new(String::with_capacity(capacity));
+    for (key, value) in pairs {
+        out.push_str(key);
+        out.push_str(separator);
+        push_double_quoted(&mut out, value);
+        out.push('\n');
+    }
+    out
+}
+
+/// Parse a YAML document that is a single flat mapping of `KEY: value`.
+///
+/// Plain scalars are taken as written (so `true` and `8080` become text),
+/// single- and double-quoted scalars are unescaped, and literal block
+/// scalars (`|`, `|-`, `|+`) may span lines. Nested mappings, sequences,
+/// flow collections, anchors, tags, folded blocks and quoted scalars that
+/// continue onto another line are rejected with the offending line. An
+/// empty mapping written as `{}`, as `to_yaml` does, gives no entries.
+pub fn parse_yaml(content: &str) -> VaultResult<Vec<EnvEntry>> {
+    let lines: Vec<&str> = content.trim_start_matches('\u{feff}').lines().collect();
+    let mut entries = Vec::new();
+    let mut i = 0;
+
+    while i < lines.len() {
+        let line = lines[i].trim_end();
+        let number = i + 1;
+        i += 1;
+
+        let trimmed = line.trim_start();
+        if trimmed.is_empty() || trimmed.starts_with('#') || (line == "---" && entries.is_empty()) {
+            continue;
+        }
+        if line == "{}" && entries.is_empty() {
+            let only_comments = lines[i..]
+                .iter()
+                .all(|next| next.trim().is_empty() || next.trim_start().starts_with('#'));
+            if !only_comments {
+                return Err(VaultError::Parse(format!("line {}: nothing may follow an empty mapping", number)));
+            }
+            break;
+        }
+        if line.len() != trimmed.len() {
+            return Err(VaultError::Parse(format!("line {}: nested YAML isn't supported", number)));
+        }
+        if trimmed.starts_with("- ") || trimmed == "-" {
+            return Err(VaultError::Parse(format!("line {}: expected a mapping, not a sequence", number)));
+        }
+
+        let (key, rest) = split_yaml_key(line).ok_or_else(|| {
+            VaultError::Parse(format!("line {}: expected KEY: value", number))
+        })?;
+        let rest = rest.trim();
+        let value = match rest.chars().next() {
+            None | Some('#') => {
+                let nested = lines[i..]
+                    .iter()
+                    .find(|next| !next.trim().is_empty() && !next.trim_start().starts_with('#'))
+                    .is_some_and(|next| next.starts_with([' ', '\t']) || next.starts_with('-'));
+                if nested {
+                    return Err(VaultError::Parse(format!("line {}: value for {} is nested", number, key)));
+                }
+                String::new()
+            }
+            Some('|') => {
+                let (value, consumed) = parse_yaml_literal(rest, &lines[i..])
+                    .ok_or_else(|| VaultError::Parse(format!("line {}: invalid block scalar", number)))?;
+                i += consumed;
+                value
+            }
+            Some('"') => parse_double_quoted_yaml(&rest[1..])
+                .filter(|(_, consumed)| is_trailing_comment(&rest[1 + consumed..]))
+                .map(|(value, _)| value)
+                .ok_or_else(|| VaultError::Parse(format!("line {}: unterminated or multi-line quoted value", number)))?,
+            Some('\'') => parse_single_quoted_yaml(&rest[1..])
+                .filter(|(_, consumed)| is_trailing_comment(&rest[1 + consumed..]))
+                .map(|(value, _)| value)
+                .ok_or_else(|| VaultError::Parse(format!("line {}: unterminated or multi-line quoted value", number)))?,
+            Some('{' | '[') => {
+                return Err(VaultError::Parse(format!("line {}: value for {} is nested", number, key)));
+            }
+            Some('>') => {
+                return Err(VaultError::Parse(format!("line {}: folded block scalars aren't supported", number)));
+            }
+            Some('&' | '*' | '!') => {
+                return Err(VaultError::Parse(format!(
+                    "line {}: anchors, aliases and tags aren't supported",
+                    number
+                )));
+            }
+            Some(_) => strip_inline_comment(rest).trim_end().to_string(),
+        };
+
+        entries.push((key, value).into());
+    }
+
+    Ok(entries)
+}
+
+/// Split `KEY: value` at the colon that ends the key, unquoting a quoted key
+fn split_yaml_key(line: &str) -> Option<(String, &str)> {
+    let (key, rest) = match line.chars().next()? {
+        '"' => {
+            let (key, consumed) = parse_double_quoted_yaml(&line[1..])?;
+            (key, &line[1 + consumed..])
+        }
+        '\'' => {
+            let (key, consumed) = parse_single_quoted_yaml(&line[1..])?;
+            (key, &line[1 + consumed..])
+        }
+        _ => {
+            let colon = line.match_indices(':').map(|(at, _)| at).find(|&at| {
+                line[at + 1..].is_empty() || line[at + 1..].starts_with([' ', '\t'])
+            })?;
+            (line[..colon].trim_end().to_string(), &line[colon..])
+        }
+    };
+
+    let rest = rest.trim_start().strip_prefix(':')?;
+    if key.is_empty() || !(rest.is_empty() || rest.starts_with([' ', '\t'])) {
+        return None;
+    }
+    Some((key, rest))
+}
+
+/// Whether what follows a quoted scalar is only whitespace or a comment
+fn is_trailing_comment(rest: &str) -> bool {
+    let rest = rest.trim_start();
+    rest.is_empty() || rest.starts_with('#')
+}
+
+/// Read the lines of a literal block scalar introduced by `header` (`|`,
+/// `|-` or `|+`, optionally followed by a comment). Returns the value and
+/// how many lines it took.
+fn parse_yaml_literal(header: &str, lines: &[&str]) -> Option<(String, usize)> {
+    let chomping = match strip_inline_comment(header).trim_end() {
+        "|" => None,
+        "|-" => Some(false),
+        "|+" => Some(true),
+        _ => return None,
+    };
+
+    // The first non-blank line sets the indentation of the whole block
+    let indent = lines
+        .iter()
+        .find(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start_matches(' ').len())
+        .filter(|&indent| indent > 0);
+
+    let mut consumed = 0;
+    let mut body: Vec<&str> = Vec::new();
+    if let Some(indent) = indent {
+        for line in lines {
+            if line.trim().is_empty() {
+                body.push("");
+            } else if line.len() - line.trim_start_matches(' ').len() >= indent {
+                body.push(&line[indent..]);
+            } else {
+                break;
+            }
+            consumed += 1;
+        }
+    }
+
+    // Blank lines after the block belong to whatever follows it
+    let content_len = body.iter().rposition(|line| !line.is_empty()).map_or(0, |last| last + 1);
+    let trailing = body.len() - content_len;
+    if chomping != Some(true) {
+        consumed -= trailing;
+    }
+
+    let mut value = body[..content_len].join("\n");
+    match chomping {
+        Some(false) => {}
+        None if content_len > 0 => value.push('\n'),
+        None => {}
+        Some(true) => value.push_str(&"\n".repeat(trailing + usize::from(content_len > 0))),
+    }
+    Some((value, consumed))
+}
+
+/// Read a YAML double-quoted scalar up to its closing quote on the same
+/// line. Returns the value and the bytes consumed including the quote.
+fn parse_double_quoted_yaml(s: &str) -> Option<(String, usize)> {
+    let mut value = String::new();
+    let mut chars = s.char_indices();
+
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return Some((value, i + 1)),
+            '\\' => {
+                let (_, escape) = chars.next()?;
+                let hex_len = match escape {
+                    'x' => 2,
+                    'u' => 4,
+                    'U' => 8,
+                    _ => 0,
+                };
+                if hex_len > 0 {
+                    let hex: String = chars.by_ref().take(hex_len).map(|(_, c)| c).collect();
+                    value.push(u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32)?);
+                    continue;
+                }
+                value.push(match escape {
+                    'n' => '\n',
+                    'r' => '\r',
+                    't' => '\t',
+                    '0' => '\0',
+                    '"' => '"',
+                    '\\' => '\\',
+                    '/' => '/',
+                    ' ' => ' ',
+                    _ => return None,
+                });
+            }
+            c => value.push(c),
+        }
+    }
+
+    None
+}
+
+/// Read a YAML single-quoted scalar, where `''` stands for a quote, up to
+/// its closing quote on the same line
+fn parse_single_quoted_yaml(s: &str) -> Option<(String, usize)> {
+    let mut value = String::new();
+    let mut chars = s.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        if c == '\'' {
+            if chars.peek().is_some_and(|&(_, next)| next == '\'') {
+                chars.next();
+                value.push('\'');
+            } else {
+                return Some((value, i + 1));
+            }
+        } else {
+            value.push(c);
+        }
+    }
+
+    None
+}
@@ -0,0 +1,86 @@
+/// Points for each query character that matches
+const MATCH: i32 = 16;
+/// Extra points when a match starts a word (`DB` and `URL` in `DB_URL`)
+const WORD_START: i32 = 8;
+/// Extra points when a match directly follows the previous one
+const CONSECUTIVE: i32 = 8;
+/// Points lost per candidate character skipped between two matches
+const GAP: i32 = 1;
+/// Points lost per unmatched character, so shorter keys rank first
+const UNMATCHED: i32 = 1;
+
+/// Whether `chars[i]` begins a word: the first character, one after a
+/// separator, or an uppercase letter following a lowercase one
+fn is_word_start(chars: &[char], i: usize) -> bool {
+    if i == 0 {
+        return true;
+    }
+    let prev = chars[i - 1];
+    !prev.is_alphanumeric() || (prev.is_lowercase() && chars[i].is_uppercase())
+}
+
+/// Score `candidate` against `query` as a case-insensitive subsequence
+/// match, or `None` if the query's characters don't all appear in order.
+/// Higher is better; matches at word starts and in runs score highest.
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<i32> {
+    let query: Vec<char> = query.chars().filter(|c| !c.is_whitespace()).flat_map(char::to_lowercase).collect();
+    let chars: Vec<char> = candidate.chars().collect();
+    let lower: Vec<char> = chars.iter().map(|c| c.to_lowercase().next().unwrap_or(*c)).collect();
+    if query.is_empty() {
+        return Some(0);
+    }
+
+    // best[j]: best score with the current query character matched at j
+    let mut best: Vec<Option<i32>> = vec![None; chars.len()];
+    for (qi, &qc) in query.iter().enumerate() {
+        let mut next = vec![None; chars.len()];
+        for j in 0..chars.len() {
+            if lower[j] != qc {
+                continue;
+            }
+            let bonus = MATCH + if is_word_start(&chars, j) { WORD_START } else { 0 };
+
+            next[j] = if qi == 0 {
+                Some(bonus)
+            } else {
+                (0..j)
+                    .filter_map(|k| {
+                        let prev = best[k]?;
+                        let link = if k + 1 == j { CONSECUTIVE } else { -GAP * (j - k - 1) as i32 };
+                        Some(prev + link)
+                    })
+                    .max()
+                    .map(|score| score + bonus)
+            };
+        }
+        best = next;
+    }
+
+    let unmatched = chars.len().saturating_sub(query.len()) as i32;
+    best.into_iter().flatten().max().map(|score| score - UNMATCHED * unmatched)
+}
+
+/// Edits needed to turn `a` into `b`, ignoring case: insertions,
+/// deletions, substitutions and swaps of two neighbouring characters
+pub fn typo_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().flat_map(char::to_lowercase).collect();
+    let b: Vec<char> = b.chars().flat_map(char::to_lowercase).collect();
+
+    // rows[i][j]: distance between a[..i] and b[..j]
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+    rows[0] = (0..=b.len()).collect();
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let substitution = rows[i - 1][j - 1] + usize::from(a[i - 1] != b[j - 1]);
+            let mut best = substitution.min(rows[i - 1][j] + 1).min(rows[i][j - 1] + 1);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                best = best.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = best;
+        }
+    }
+    rows[a.len()][b.len()]
+}
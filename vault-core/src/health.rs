@@ -0,0 +1,221 @@
+//! Health checks on the vault: values shared between secrets, weak values,
+//! and a damaged database file or values that no longer decrypt.
+
+use crate::error::ErrorCode;
+use crate::namespace_keys::VaultKeys;
+use crate::{crypto, open_value, require_key, with_conn, DEFAULT_NAMESPACE};
+use napi_derive::napi;
+use ring::digest::{digest, SHA256};
+use std::collections::HashMap;
+use zeroize::Zeroizing;
+
+/// Secrets that share one value, as returned to JavaScript
+#[napi(object)]
+pub struct DuplicateGroup {
+    pub ids: Vec<u32>,
+    pub keys: Vec<String>,
+    /// Namespace of each key, in the same order
+    pub namespaces: Vec<String>,
+}
+
+/// Find secrets in any namespace that hold identical values. Values are
+/// compared by SHA-256 digest inside the core and never returned.
+/// Namespaces with their own password are only compared while unlocked.
+/// Fails with `Locked` while the vault is locked.
+#[napi]
+pub fn find_duplicate_values() -> napi::Result<Vec<DuplicateGroup>, ErrorCode> {
+    let groups = with_conn(|conn| {
+        require_key(conn)?;
+        let keys = VaultKeys::load(conn)?;
+        let rows: Vec<(u32, String, String, String, String, bool)> = conn
+            .prepare("SELECT id, namespace, key, value, nonce, compressed FROM secrets ORDER BY namespace ASC, key ASC")?
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?, row.get(5)?)))?
+            .collect::<Result<_, _>>()?;
+
+        let mut by_digest: HashMap<Vec<u8>, Vec<(u32, String, String)>> = HashMap::new();
+        let mut order = Vec::new();
+        for (id, namespace, name, value, nonce, compressed) in rows {
+            let Some(plaintext) = keys.get(&namespace).and_then(|key| open_value(key, &value, &nonce, compressed)) else {
+                continue;
+            };
+            let hash = digest(&SHA256, plaintext.as_bytes()).as_ref().to_vec();
+            let group = by_digest.entry(hash.clone()).or_default();
+            if group.is_empty() {
+                order.push(hash);
+            }
+            group.push((id, name, namespace));
+        }
+
+        // Groups come out in the order their first key sorts
+        Ok(order
+            .into_iter()
+            .filter_map(|hash| by_digest.remove(&hash))
+            .filter(|group| group.len() > 1)
+            .map(|group| DuplicateGroup {
+                ids: group.iter().map(|(id, _, _)| *id).collect(),
+                keys: group.iter().map(|(_, name, _)| name.clone()).collect(),
+                namespaces: group.into_iter().map(|(_, _, namespace)| namespace).collect(),
+            })
+            .collect())
+    })?;
+
+    Ok(groups)
+}
+
+/// Strength assessment of a value, as returned to JavaScript
+#[napi(object)]
+pub struct StrengthReport {
+    /// 0 (trivially guessable) to 4 (very strong)
+    pub score: u32,
+    /// Estimated entropy in bits
+    pub entropy_bits: f64,
+    /// Why the score isn't higher; empty for a strong value
+    pub reasons: Vec<String>,
+}
+
+/// Passwords common enough to be tried first by any attacker
+const COMMON_PASSWORDS: &[&str] = &[
+    "123456", "password", "12345678", "qwerty", "123456789", "12345", "1234", "111111",
+    "1234567", "dragon", "123123", "baseball", "abc123", "football", "monkey", "letmein",
+    "696969", "shadow", "master", "666666", "qwertyuiop", "123321", "mustang", "1234567890",
+    "michael", "654321", "superman", "1qaz2wsx", "7777777", "121212", "000000", "qazwsx",
+    "123qwe", "killer", "trustno1", "jordan", "jennifer", "zxcvbnm", "asdfgh", "hunter",
+    "buster", "soccer", "harley", "batman", "andrew", "tigger", "sunshine", "iloveyou",
+    "admin", "welcome", "changeme", "secret", "passw0rd", "p@ssw0rd", "root", "test",
+];
+
+/// Length below which a value is flagged as short
+const MIN_STRONG_LEN: usize = 12;
+
+/// Whether `chars` is mostly a run of one character or a straight
+/// ascending/descending sequence like `abcd` or `4321`
+fn is_patterned(chars: &[char]) -> bool {
+    if chars.len() < 3 {
+        return false;
+    }
+    let steps: Vec<i64> = chars.windows(2).map(|w| w[1] as i64 - w[0] as i64).collect();
+    let patterned = steps.iter().filter(|&&step| step.abs() <= 1).count();
+    patterned * 4 >= steps.len() * 3
+}
+
+/// Estimate how hard `value` would be to guess, from its length, the
+/// character classes it draws on and whether it is a common password
+pub fn assess_strength(value: &str) -> StrengthReport {
+    let chars: Vec<char> = value.chars().collect();
+    let mut reasons = Vec::new();
+
+    let classes = [
+        (chars.iter().any(|c| c.is_ascii_lowercase()), 26),
+        (chars.iter().any(|c| c.is_ascii_uppercase()), 26),
+        (chars.iter().any(|c| c.is_ascii_digit()), 10),
+        (chars.iter().any(|c| c.is_ascii_punctuation() || *c == ' '), 33),
+        (chars.iter().any(|c| !c.is_ascii()), 100),
+    ];
+    let pool: u32 = classes.iter().filter(|(present, _)| *present).map(|(_, size)| size).sum();
+    let class_count = classes.iter().filter(|(present, _)| *present).count();
+    let mut entropy_bits = if pool == 0 { 0.0 } else { chars.len() as f64 * f64::from(pool).log2() };
+
+    if chars.len() < MIN_STRONG_LEN {
+        reasons.push(format!("shorter than {} characters", MIN_STRONG_LEN));
+    }
+    if class_count == 1 {
+        reasons.push("uses only one kind of character".to_string());
+    }
+    if is_patterned(&chars) {
+        reasons.push("is a repeated or sequential pattern".to_string());
+        entropy_bits /= 4.0;
+    }
+
+    let lower = value.to_lowercase();
+    let common = COMMON_PASSWORDS.iter().any(|common| *common == lower);
+    if common {
+        reasons.push("matches a common password".to_string());
+        entropy_bits = entropy_bits.min(10.0);
+    } else if let Some(word) = COMMON_PASSWORDS.iter().filter(|c| c.len() >= 5).find(|c| lower.contains(**c)) {
+        reasons.push(format!("contains the common password {:?}", word));
+        entropy_bits -= 8.0 * word.len() as f64 / 2.0;
+    }
+    let entropy_bits = entropy_bits.max(0.0);
+
+    let score = match entropy_bits {
+        bits if common || bits < 28.0 => 0,
+        bits if bits < 36.0 => 1,
+        bits if bits < 60.0 => 2,
+        bits if bits < 80.0 => 3,
+        _ => 4,
+    };
+
+    StrengthReport { score, entropy_bits, reasons }
+}
+
+/// Estimate the strength of a value about to be stored
+#[napi]
+pub fn estimate_strength(value: String) -> StrengthReport {
+    assess_strength(&Zeroizing::new(value))
+}
+
+/// Result of `check_integrity`, as returned to JavaScript
+#[napi(object)]
+pub struct IntegrityReport {
+    /// Whether SQLite's `integrity_check` found the file sound
+    pub database_ok: bool,
+    /// Problems `integrity_check` reported, if any
+    pub database_errors: Vec<String>,
+    pub secret_count: u32,
+    /// Whether values were test-decrypted; false while the vault is locked.
+    /// Secrets in namespaces still locked with their own password are
+    /// skipped either way.
+    pub values_checked: bool,
+    /// Secrets whose stored value doesn't decrypt under the current key.
+    /// Every secret failing points at the wrong key; a few point at
+    /// damaged rows.
+    pub undecryptable_ids: Vec<u32>,
+}
+
+/// Check the vault for corruption: run SQLite's integrity check and, when
+/// the vault is unlocked, try to decrypt every value
+#[napi]
+pub fn check_integrity() -> napi::Result<IntegrityReport, ErrorCode> {
+    let report = with_conn(|conn| {
+        let messages: Vec<String> = conn
+            .prepare("PRAGMA integrity_check")?
+            .query_map([], |row| row.get(0))?
+            .collect::<Result<_, _>>()?;
+        let database_ok = messages.len() == 1 && messages[0] == "ok";
+        let secret_count = conn.query_row("SELECT COUNT(*) FROM secrets", [], |row| row.get(0))?;
+
+        let keys = VaultKeys::load(conn)?;
+        let values_checked = keys.get(DEFAULT_NAMESPACE).is_some();
+        let mut undecryptable_ids = Vec::new();
+        if values_checked {
+            let mut stmt = conn.prepare("SELECT id, value, nonce, namespace FROM secrets ORDER BY id ASC")?;
+            let rows = stmt.query_map([], |row| {
+                Ok((
+                    row.get::<_, u32>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, Option<String>>(2)?,
+                    row.get::<_, String>(3)?,
+                ))
+            })?;
+            for row in rows {
+                let (id, value, nonce, namespace) = row?;
+                let Some(key) = keys.get(&namespace) else {
+                    continue;
+                };
+                if nonce.is_none_or(|nonce| crypto::decrypt(key, &value, &nonce).is_none()) {
+                    undecryptable_ids.push(id);
+                }
+            }
+        }
+
+        Ok(IntegrityReport {
+            database_ok,
+            database_errors: if database_ok { vec![] } else { messages },
+            secret_count,
+            values_checked,
+            undecryptable_ids,
+        })
+    })?;
+
+    Ok(report)
+}
@@ -0,0 +1,99 @@
+use crate::error::{VaultError, VaultResult};
+use crate::is_valid_key;
+use std::collections::HashMap;
+use zeroize::Zeroizing;
+
+/// Deepest chain of references followed before giving up, so a long chain
+/// can't exhaust the stack
+const MAX_REFERENCE_DEPTH: usize = 32;
+
+/// Expands `${KEY}` references in values with the values of other secrets,
+/// which may hold references themselves. `lookup` fetches a referenced
+/// secret's stored value, or `None` if there is no such secret. Anything
+/// that isn't `${` followed by a valid key and `}` is left as it is.
+pub struct Resolver<F> {
+    lookup: F,
+    /// Fail on a reference to a missing secret instead of leaving it literal
+    strict: bool,
+    /// Fully expanded values, so each secret is looked up once
+    resolved: HashMap<String, Zeroizing<String>>,
+}
+
+impl<F: FnMut(&str) -> VaultResult<Option<Zeroizing<String>>>> Resolver<F> {
+    pub fn new(strict: bool, lookup: F) -> Self {
+        Resolver { lookup, strict, resolved: HashMap::new() }
+    }
+
+    /// Expand the references in `value`, the value of secret `key`. Fails
+    /// if the references lead back to a secret already being expanded.
+    pub fn resolve(&mut self, key: &str, value: &str) -> VaultResult<Zeroizing<String>> {
+        let mut chain = vec![key.to_string()];
+        let expanded = self.expand(value, &mut chain)?;
+        self.resolved.insert(key.to_string(), expanded.clone());
+        Ok(expanded)
+    }
+
+    /// Expand `value`, where `chain` lists the secrets whose expansion led here
+    fn expand(&mut self, value: &str, chain: &mut Vec<String>) -> VaultResult<Zeroizing<String>> {
+        let mut out = Zeroizing::new(String::with_capacity(value.len()));
+        let mut rest = value;
+
+        while let Some(start) = rest.find("${") {
+            out.push_str(&rest[..start]);
+            let reference = &rest[start..];
+            let Some(end) = reference.find('}') else {
+                rest = reference;
+                break;
+            };
+            let name = &reference[2..end];
+            rest = &reference[end + 1..];
+
+            if !is_valid_key(name) {
+                out.push_str(&reference[..=end]);
+                continue;
+            }
+            match self.value_of(name, chain)? {
+                Some(value) => out.push_str(&value),
+                None if self.strict => {
+                    return Err(VaultError::InvalidArgument(format!(
+                        "{} refers to {}, which doesn't exist",
+                        chain.last().map_or("", String::as_str),
+                        name
+                    )));
+                }
+                None => out.push_str(&reference[..=end]),
+            }
+        }
+
+        out.push_str(rest);
+        Ok(out)
+    }
+
+    /// The expanded value of secret `name`, if it exists
+    fn value_of(&mut self, name: &str, chain: &mut Vec<String>) -> VaultResult<Option<Zeroizing<String>>> {
+        if let Some(value) = self.resolved.get(name) {
+            return Ok(Some(value.clone()));
+        }
+        if let Some(start) = chain.iter().position(|key| key == name) {
+            let mut cycle = chain[start..].to_vec();
+            cycle.push(name.to_string());
+            return Err(VaultError::InvalidArgument(format!("reference cycle: {}", cycle.join(" -> "))));
+        }
+        if chain.len() > MAX_REFERENCE_DEPTH {
+            return Err(VaultError::InvalidArgument(format!(
+                "references nested more than {} deep at {}",
+                MAX_REFERENCE_DEPTH, name
+            )));
+        }
+
+        let Some(raw) = (self.lookup)(name)? else {
+            return Ok(None);
+        };
+        chain.push(name.to_string());
+        let expanded = self.expand(&raw, chain)?;
+        chain.pop();
+
+        self.resolved.insert(name.to_string(), expanded.clone());
+        Ok(Some(expanded))
+    }
+}
@@ -0,0 +1,78 @@
+use crate::error::{ErrorCode, VaultError, VaultResult};
+use crate::{
+    cache_master_key, cached_master_key, crypto, get_current_db_path, is_in_memory, key_matches_verifier,
+    master_password_set, with_conn,
+};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use napi_derive::napi;
+use zeroize::Zeroizing;
+
+/// Service name the key is filed under in the keychain
+const KEYCHAIN_SERVICE: &str = "EnvVault";
+
+fn keychain_err(err: keyring::Error) -> VaultError {
+    VaultError::Keychain(err.to_string())
+}
+
+/// Keychain entry for the current vault. Entries are named after the
+/// database path so separate vaults never share one.
+fn entry() -> VaultResult<keyring::Entry> {
+    if is_in_memory() {
+        return Err(VaultError::InvalidArgument("an in-memory vault can't use the keychain".to_string()));
+    }
+    keyring::Entry::new(KEYCHAIN_SERVICE, &get_current_db_path()).map_err(keychain_err)
+}
+
+/// Save the unlocked master key in the OS keychain (macOS Keychain,
+/// Windows Credential Manager or the Secret Service) so later launches can
+/// unlock with `load_key_from_keychain`. The derived key is stored, never
+/// the password. Fails with `Locked` unless the vault is unlocked.
+#[napi]
+pub fn store_key_in_keychain() -> napi::Result<(), ErrorCode> {
+    let key = with_conn(|conn| {
+        if !master_password_set(conn) {
+            return Err(VaultError::InvalidArgument("no master password is set".to_string()));
+        }
+        cached_master_key().ok_or(VaultError::Locked)
+    })?;
+
+    let encoded = Zeroizing::new(BASE64.encode(key.as_slice()));
+    entry()?.set_password(&encoded).map_err(keychain_err)?;
+    Ok(())
+}
+
+/// Unlock the vault with a key saved by `store_key_in_keychain`. Returns
+/// false when nothing is stored or the stored key no longer matches (for
+/// example after `change_master_password`), so the caller can fall back
+/// to asking for the password.
+#[napi]
+pub fn load_key_from_keychain() -> napi::Result<bool, ErrorCode> {
+    let encoded = match entry()?.get_password() {
+        Ok(encoded) => Zeroizing::new(encoded),
+        Err(keyring::Error::NoEntry) => return Ok(false),
+        Err(err) => return Err(keychain_err(err).into()),
+    };
+    let Some(key) = BASE64
+        .decode(&*encoded)
+        .ok()
+        .map(Zeroizing::new)
+        .and_then(|bytes| <[u8; crypto::KEY_LEN]>::try_from(bytes.as_slice()).ok())
+        .map(Zeroizing::new)
+    else {
+        return Ok(false);
+    };
+
+    let unlocked = with_conn(|conn| {
+        Ok(master_password_set(conn) && key_matches_verifier(conn, &key) && cache_master_key(key, None))
+    })?;
+    Ok(unlocked)
+}
+
+/// Remove the key saved by `store_key_in_keychain`, if any
+#[napi]
+pub fn forget_keychain_key() -> napi::Result<(), ErrorCode> {
+    match entry()?.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(err) => Err(keychain_err(err).into()),
+    }
+}
@@ -0,0 +1,326 @@
+use crate::error::{VaultError, VaultResult};
+use rusqlite::{params, Connection, Transaction};
+
+/// A single schema change, run inside its own transaction
+type Migration = fn(&Transaction) -> VaultResult<()>;
+
+/// Every schema change in order; a vault at `PRAGMA user_version` n has
+/// had the first n applied. Append new migrations, never edit old ones.
+///
+/// Vaults created before versioning start at 0 with some of these changes
+/// already made, so the early migrations check before altering anything.
+const MIGRATIONS: &[Migration] = &[
+    // 1: the original table
+    |tx| {
+        tx.execute(
+            "CREATE TABLE IF NOT EXISTS secrets (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                key TEXT NOT NULL UNIQUE,
+                value TEXT NOT NULL,
+                created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+            )",
+            [],
+        )?;
+        Ok(())
+    },
+    // 2: encrypted values carry a nonce; rows without one are still plaintext
+    |tx| {
+        if !has_column(tx, "secrets", "nonce")? {
+            tx.execute("ALTER TABLE secrets ADD COLUMN nonce TEXT", [])?;
+        }
+        Ok(())
+    },
+    // 3: master password salt, KDF parameters and settings
+    |tx| {
+        tx.execute(
+            "CREATE TABLE IF NOT EXISTS vault_meta (
+                name TEXT PRIMARY KEY,
+                value TEXT NOT NULL
+            )",
+            [],
+        )?;
+        Ok(())
+    },
+    // 4: namespaces; keys were globally unique before, so the table is
+    // rebuilt with the (namespace, key) constraint
+    |tx| {
+        if !has_column(tx, "secrets", "namespace")? {
+            tx.execute_batch(
+                "CREATE TABLE secrets_new (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    namespace TEXT NOT NULL DEFAULT 'default',
+                    key TEXT NOT NULL,
+                    value TEXT NOT NULL,
+                    nonce TEXT,
+                    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                    UNIQUE (namespace, key)
+                );
+                INSERT INTO secrets_new (id, namespace, key, value, nonce, created_at, updated_at)
+                    SELECT id, 'default', key, value, nonce, created_at, updated_at FROM secrets;
+                DROP TABLE secrets;
+                ALTER TABLE secrets_new RENAME TO secrets;",
+            )?;
+        }
+        Ok(())
+    },
+    // 5: previous values of each secret
+    |tx| {
+        tx.execute(
+            "CREATE TABLE IF NOT EXISTS secret_history (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                secret_id INTEGER NOT NULL REFERENCES secrets(id) ON DELETE CASCADE,
+                value TEXT NOT NULL,
+                nonce TEXT NOT NULL,
+                changed_at DATETIME DEFAULT CURRENT_TIMESTAMP
+            )",
+            [],
+        )?;
+        Ok(())
+    },
+    // 6: tags
+    |tx| {
+        tx.execute_batch(
+            "CREATE TABLE IF NOT EXISTS tags (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                name TEXT NOT NULL UNIQUE
+            );
+            CREATE TABLE IF NOT EXISTS secret_tags (
+                secret_id INTEGER NOT NULL REFERENCES secrets(id) ON DELETE CASCADE,
+                tag_id INTEGER NOT NULL REFERENCES tags(id) ON DELETE CASCADE,
+                PRIMARY KEY (secret_id, tag_id)
+            );",
+        )?;
+        Ok(())
+    },
+    // 7: descriptions; existing rows are left with NULL
+    |tx| {
+        if !has_column(tx, "secrets", "description")? {
+            tx.execute("ALTER TABLE secrets ADD COLUMN description TEXT", [])?;
+        }
+        Ok(())
+    },
+    // 8: expiry, which stays NULL (never expires) until set
+    |tx| {
+        if !has_column(tx, "secrets", "expires_at")? {
+            tx.execute("ALTER TABLE secrets ADD COLUMN expires_at DATETIME", [])?;
+        }
+        Ok(())
+    },
+    // 9: audit log; rows outlive the secrets they mention, so no foreign key
+    |tx| {
+        tx.execute(
+            "CREATE TABLE IF NOT EXISTS audit_log (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                operation TEXT NOT NULL,
+                secret_id INTEGER,
+                namespace TEXT,
+                key TEXT,
+                at DATETIME DEFAULT CURRENT_TIMESTAMP
+            )",
+            [],
+        )?;
+        Ok(())
+    },
+    // 10: when each value was last revealed; NULL until the first reveal
+    |tx| {
+        if !has_column(tx, "secrets", "last_accessed_at")? {
+            tx.execute("ALTER TABLE secrets ADD COLUMN last_accessed_at DATETIME", [])?;
+        }
+        Ok(())
+    },
+    // 11: position of each key in the file it was last imported from
+    |tx| {
+        if !has_column(tx, "secrets", "env_position")? {
+            tx.execute("ALTER TABLE secrets ADD COLUMN env_position INTEGER", [])?;
+        }
+        Ok(())
+    },
+    // 12: revision counter bumped by any change to what the lists show;
+    // reads only touch last_accessed_at, so they leave it alone
+    |tx| {
+        let bump = "INSERT INTO vault_meta (name, value) VALUES ('revision', '1')
+            ON CONFLICT (name) DO UPDATE SET value = CAST(value AS INTEGER) + 1";
+        tx.execute_batch(&format!(
+            "CREATE TRIGGER IF NOT EXISTS secrets_revision_insert AFTER INSERT ON secrets
+                BEGIN {bump}; END;
+            CREATE TRIGGER IF NOT EXISTS secrets_revision_update
+                AFTER UPDATE OF namespace, key, value, nonce, description, expires_at ON secrets
+                BEGIN {bump}; END;
+            CREATE TRIGGER IF NOT EXISTS secrets_revision_delete AFTER DELETE ON secrets
+                BEGIN {bump}; END;
+            CREATE TRIGGER IF NOT EXISTS secret_tags_revision_insert AFTER INSERT ON secret_tags
+                BEGIN {bump}; END;
+            CREATE TRIGGER IF NOT EXISTS secret_tags_revision_delete AFTER DELETE ON secret_tags
+                BEGIN {bump}; END;",
+            bump = bump
+        ))?;
+        Ok(())
+    },
+    // 13: read-only flag guarding against accidental edits, which the
+    // revision trigger now watches as well
+    |tx| {
+        if !has_column(tx, "secrets", "readonly")? {
+            tx.execute("ALTER TABLE secrets ADD COLUMN readonly INTEGER NOT NULL DEFAULT 0", [])?;
+        }
+        tx.execute_batch(
+            "DROP TRIGGER IF EXISTS secrets_revision_update;
+            CREATE TRIGGER secrets_revision_update
+                AFTER UPDATE OF namespace, key, value, nonce, description, expires_at, readonly ON secrets
+                BEGIN
+                    INSERT INTO vault_meta (name, value) VALUES ('revision', '1')
+                    ON CONFLICT (name) DO UPDATE SET value = CAST(value AS INTEGER) + 1;
+                END;",
+        )?;
+        Ok(())
+    },
+    // 14: whether a value holds raw bytes rather than UTF-8 text
+    |tx| {
+        if !has_column(tx, "secrets", "binary")? {
+            tx.execute("ALTER TABLE secrets ADD COLUMN binary INTEGER NOT NULL DEFAULT 0", [])?;
+        }
+        Ok(())
+    },
+    // 15: the revision trigger also watches updated_at, which `touch_secret`
+    // can change on its own
+    |tx| {
+        tx.execute_batch(
+            "DROP TRIGGER IF EXISTS secrets_revision_update;
+            CREATE TRIGGER secrets_revision_update
+                AFTER UPDATE OF namespace, key, value, nonce, description, expires_at, readonly, updated_at
+                ON secrets
+                BEGIN
+                    INSERT INTO vault_meta (name, value) VALUES ('revision', '1')
+                    ON CONFLICT (name) DO UPDATE SET value = CAST(value AS INTEGER) + 1;
+                END;",
+        )?;
+        Ok(())
+    },
+    // 16: justification recorded with a reveal, if one was asked for
+    |tx| {
+        if !has_column(tx, "audit_log", "reason")? {
+            tx.execute("ALTER TABLE audit_log ADD COLUMN reason TEXT", [])?;
+        }
+        Ok(())
+    },
+    // 17: namespaces protected by their own password, with the salt, cost
+    // and verifier of the key their secrets are encrypted under
+    |tx| {
+        tx.execute(
+            "CREATE TABLE IF NOT EXISTS namespace_keys (
+                namespace TEXT PRIMARY KEY,
+                kdf_salt TEXT NOT NULL,
+                kdf_memory_kib INTEGER NOT NULL,
+                kdf_iterations INTEGER NOT NULL,
+                kdf_parallelism INTEGER NOT NULL,
+                verifier TEXT NOT NULL,
+                verifier_nonce TEXT NOT NULL
+            )",
+            [],
+        )?;
+        Ok(())
+    },
+    // 18: whether a value was compressed before it was encrypted, on
+    // secrets and the history entries copied from them
+    |tx| {
+        for table in ["secrets", "secret_history"] {
+            if !has_column(tx, table, "compressed")? {
+                tx.execute(&format!("ALTER TABLE {} ADD COLUMN compressed INTEGER NOT NULL DEFAULT 0", table), [])?;
+            }
+        }
+        Ok(())
+    },
+    // 19: other names a secret answers to. Keys and aliases share one
+    // namespace, so a secret can't be added, renamed or moved onto an alias.
+    |tx| {
+        tx.execute_batch(
+            "CREATE TABLE IF NOT EXISTS secret_aliases (
+                secret_id INTEGER NOT NULL REFERENCES secrets(id) ON DELETE CASCADE,
+                alias TEXT NOT NULL,
+                PRIMARY KEY (secret_id, alias)
+            );
+            CREATE TRIGGER IF NOT EXISTS secrets_alias_insert BEFORE INSERT ON secrets
+                WHEN EXISTS (SELECT 1 FROM secret_aliases a JOIN secrets s ON s.id = a.secret_id
+                    WHERE s.namespace = NEW.namespace AND a.alias = NEW.key)
+                BEGIN SELECT RAISE(ABORT, 'UNIQUE constraint failed: key is an alias'); END;
+            CREATE TRIGGER IF NOT EXISTS secrets_alias_update BEFORE UPDATE OF namespace, key ON secrets
+                WHEN EXISTS (SELECT 1 FROM secret_aliases a JOIN secrets s ON s.id = a.secret_id
+                    WHERE s.namespace = NEW.namespace AND s.id != NEW.id AND a.alias = NEW.key)
+                OR EXISTS (SELECT 1 FROM secret_aliases a JOIN secrets s
+                    ON s.namespace = NEW.namespace AND s.id != NEW.id
+                    AND (s.key = a.alias OR EXISTS (SELECT 1 FROM secret_aliases b WHERE b.secret_id = s.id AND b.alias = a.alias))
+                    WHERE a.secret_id = NEW.id)
+                BEGIN SELECT RAISE(ABORT, 'UNIQUE constraint failed: key is an alias'); END;",
+        )?;
+        Ok(())
+    },
+    // 20: section of the .env file each key was last imported under
+    |tx| {
+        if !has_column(tx, "secrets", "env_group")? {
+            tx.execute("ALTER TABLE secrets ADD COLUMN env_group TEXT", [])?;
+        }
+        Ok(())
+    },
+    // 21: aliases change what lookups and exports return, so adding or
+    // removing one bumps the revision too
+    |tx| {
+        let bump = "INSERT INTO vault_meta (name, value) VALUES ('revision', '1')
+            ON CONFLICT (name) DO UPDATE SET value = CAST(value AS INTEGER) + 1";
+        tx.execute_batch(&format!(
+            "CREATE TRIGGER IF NOT EXISTS secret_aliases_revision_insert AFTER INSERT ON secret_aliases
+                BEGIN {bump}; END;
+            CREATE TRIGGER IF NOT EXISTS secret_aliases_revision_delete AFTER DELETE ON secret_aliases
+                BEGIN {bump}; END;",
+            bump = bump
+        ))?;
+        Ok(())
+    },
+    // 22: whether a history entry holds raw bytes, so a binary secret
+    // overwritten with text can be reverted to its bytes
+    |tx| {
+        if !has_column(tx, "secret_history", "binary")? {
+            tx.execute("ALTER TABLE secret_history ADD COLUMN binary INTEGER NOT NULL DEFAULT 0", [])?;
+        }
+        Ok(())
+    },
+];
+
+/// Schema version of a fully migrated vault
+pub const SCHEMA_VERSION: u32 = MIGRATIONS.len() as u32;
+
+/// Check whether `table` has a column called `column`
+fn has_column(conn: &Connection, table: &str, column: &str) -> VaultResult<bool> {
+    Ok(conn
+        .prepare("SELECT 1 FROM pragma_table_info(?1) WHERE name = ?2")?
+        .exists(params![table, column])?)
+}
+
+/// Run one migration and bump `user_version` to `target` in a transaction
+fn apply_migration(conn: &mut Connection, migration: Migration, target: u32) -> VaultResult<()> {
+    let tx = conn.transaction()?;
+    migration(&tx)?;
+    tx.pragma_update(None, "user_version", target)?;
+    tx.commit()?;
+    Ok(())
+}
+
+/// Bring the schema up to `SCHEMA_VERSION`, applying each pending migration
+/// and its version bump in one transaction so a failure leaves the vault at
+/// the last version that completed
+pub fn run_migrations(conn: &mut Connection) -> VaultResult<()> {
+    let version: u32 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+    if version > SCHEMA_VERSION {
+        return Err(VaultError::InvalidArgument(format!(
+            "vault schema version {} is newer than this build supports ({})",
+            version, SCHEMA_VERSION
+        )));
+    }
+
+    for (applied, migration) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+        let target = applied as u32 + 1;
+        apply_migration(conn, *migration, target).map_err(|err| VaultError::Migration(target, Box::new(err)))?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,286 @@
+use crate::error::{ErrorCode, VaultError, VaultResult};
+use crate::{
+    crypto, kdf_params, normalize_namespace, reencrypt_where, require_key, throttle_unlock, vault_key, with_conn,
+    DEFAULT_NAMESPACE, VERIFIER_PLAINTEXT,
+};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use napi_derive::napi;
+use rusqlite::{params, Connection, OptionalExtension};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+use zeroize::Zeroizing;
+
+/// Keys of the namespaces unlocked with their own password, kept until
+/// `lock_namespace` or `lock`
+static NAMESPACE_KEYS: Mutex<BTreeMap<String, crypto::SecretKey>> = Mutex::new(BTreeMap::new());
+
+/// Salt, cost and verifier of a namespace's own key
+struct StoredKey {
+    salt: Vec<u8>,
+    params: crypto::KdfParams,
+    verifier: String,
+    verifier_nonce: String,
+}
+
+impl StoredKey {
+    /// Derive the key `password` gives and keep it if it opens the verifier
+    fn derive(&self, password: &str) -> Option<crypto::SecretKey> {
+        let key = crypto::derive_key(password, &self.salt, self.params)?;
+        crypto::decrypt(&key, &self.verifier, &self.verifier_nonce)
+            .is_some_and(|plaintext| plaintext.as_slice() == VERIFIER_PLAINTEXT)
+            .then_some(key)
+    }
+}
+
+/// Read the stored key of `namespace`, if it has its own password
+fn stored_key(conn: &Connection, namespace: &str) -> VaultResult<Option<StoredKey>> {
+    let row = conn
+        .query_row(
+            "SELECT kdf_salt, kdf_memory_kib, kdf_iterations, kdf_parallelism, verifier, verifier_nonce
+             FROM namespace_keys WHERE namespace = ?1",
+            params![namespace],
+            |row| {
+                let params = crypto::KdfParams {
+                    memory_kib: row.get(1)?,
+                    iterations: row.get(2)?,
+                    parallelism: row.get(3)?,
+                };
+                Ok((row.get::<_, String>(0)?, params, row.get(4)?, row.get(5)?))
+            },
+        )
+        .optional()?;
+
+    row.map(|(salt, params, verifier, verifier_nonce)| {
+        let salt = BASE64
+            .decode(salt)
+            .map_err(|_| VaultError::Crypto(format!("stored salt for namespace {} is corrupt", namespace)))?;
+        Ok(StoredKey { salt, params, verifier, verifier_nonce })
+    })
+    .transpose()
+}
+
+/// Whether `namespace` has its own password
+pub(crate) fn has_own_key(conn: &Connection, namespace: &str) -> VaultResult<bool> {
+    Ok(conn
+        .prepare("SELECT 1 FROM namespace_keys WHERE namespace = ?1")?
+        .exists(params![namespace])?)
+}
+
+/// The unlocked key of a namespace with its own password
+fn cached_key(namespace: &str) -> Option<crypto::SecretKey> {
+    NAMESPACE_KEYS.lock().ok()?.get(namespace).cloned()
+}
+
+/// Keep the key of an unlocked namespace
+fn cache_key(namespace: &str, key: crypto::SecretKey) {
+    if let Ok(mut keys) = NAMESPACE_KEYS.lock() {
+        keys.insert(namespace.to_string(), key);
+    }
+}
+
+/// Forget every unlocked namespace key
+pub(crate) fn wipe_namespace_keys() {
+    if let Ok(mut keys) = NAMESPACE_KEYS.lock() {
+        keys.clear();
+    }
+}
+
+/// Get the key secrets in `namespace` are encrypted under: its own when it
+/// has a password, which must have been given to `unlock_namespace`, and
+/// the vault key otherwise
+pub(crate) fn namespace_key(conn: &Connection, namespace: &str) -> VaultResult<crypto::SecretKey> {
+    if has_own_key(conn, namespace)? {
+        cached_key(namespace).ok_or_else(|| VaultError::NamespaceLocked(namespace.to_string()))
+    } else {
+        require_key(conn)
+    }
+}
+
+/// Keys for every namespace, gathered once by the calls that read rows
+/// from more than one
+pub(crate) struct VaultKeys {
+    vault: Option<crypto::SecretKey>,
+    /// Namespaces with their own password, and their key once unlocked
+    own: HashMap<String, Option<crypto::SecretKey>>,
+}
+
+impl VaultKeys {
+    pub(crate) fn load(conn: &Connection) -> VaultResult<Self> {
+        let namespaces: Vec<String> = conn
+            .prepare("SELECT namespace FROM namespace_keys")?
+            .query_map([], |row| row.get(0))?
+            .collect::<Result<_, _>>()?;
+        let own = namespaces
+            .into_iter()
+            .map(|namespace| {
+                let key = cached_key(&namespace);
+                (namespace, key)
+            })
+            .collect();
+
+        Ok(VaultKeys { vault: vault_key(conn), own })
+    }
+
+    /// Key for secrets in `namespace`; `None` while it is locked
+    pub(crate) fn get(&self, namespace: &str) -> Option<&[u8; crypto::KEY_LEN]> {
+        match self.own.get(namespace) {
+            Some(key) => key.as_deref(),
+            None => self.vault.as_deref(),
+        }
+    }
+
+    /// Key for secrets in `namespace`, or the error saying what is locked
+    pub(crate) fn require(&self, namespace: &str) -> VaultResult<&[u8; crypto::KEY_LEN]> {
+        self.get(namespace).ok_or_else(|| {
+            if self.own.contains_key(namespace) {
+                VaultError::NamespaceLocked(namespace.to_string())
+            } else {
+                VaultError::Locked
+            }
+        })
+    }
+}
+
+/// Fail unless `namespace` is one that may have its own password
+fn check_protectable(namespace: &str) -> VaultResult<()> {
+    if namespace == DEFAULT_NAMESPACE {
+        return Err(VaultError::InvalidArgument(
+            "the default namespace is protected by the master password".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Protect `namespace` with its own password. Its secrets and their
+/// history are re-encrypted, in one transaction, under a key derived from
+/// `password` with the vault's current Argon2id cost, and stay readable
+/// only while the namespace is unlocked. The namespace is left unlocked.
+/// Secrets added to it later are encrypted under the same key. The default
+/// namespace can't have its own password. Fails with `Locked` while the
+/// vault is locked.
+#[napi]
+pub fn set_namespace_password(namespace: String, password: String) -> napi::Result<(), ErrorCode> {
+    let password = Zeroizing::new(password);
+    let namespace = normalize_namespace(&namespace);
+    check_protectable(&namespace)?;
+    if password.is_empty() {
+        return Err(VaultError::InvalidArgument("namespace password must not be empty".to_string()).into());
+    }
+
+    with_conn(|conn| {
+        if has_own_key(conn, &namespace)? {
+            return Err(VaultError::InvalidArgument(format!(
+                "namespace {} already has its own password",
+                namespace
+            )));
+        }
+        let old_key = require_key(conn)?;
+
+        let crypto_err = || VaultError::Crypto("failed to derive namespace key".to_string());
+        let salt = crypto::random_salt().ok_or_else(crypto_err)?;
+        let params = kdf_params(conn);
+        let key = crypto::derive_key(&password, &salt, params).ok_or_else(crypto_err)?;
+        let (verifier, verifier_nonce) = crypto::encrypt(&key, VERIFIER_PLAINTEXT).ok_or_else(crypto_err)?;
+
+        let tx = conn.transaction()?;
+        reencrypt_where(&tx, "namespace = ?1", params![namespace], &old_key, &key)?;
+        tx.execute(
+            "INSERT INTO namespace_keys
+                (namespace, kdf_salt, kdf_memory_kib, kdf_iterations, kdf_parallelism, verifier, verifier_nonce)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                namespace,
+                BASE64.encode(salt),
+                params.memory_kib,
+                params.iterations,
+                params.parallelism,
+                verifier,
+                verifier_nonce
+            ],
+        )?;
+        tx.commit()?;
+
+        cache_key(&namespace, key);
+        Ok(())
+    })?;
+
+    crate::undo::forget();
+    Ok(())
+}
+
+/// Check `password` against the namespace's own, throttled like `unlock`
+fn attempt_namespace_password(conn: &Connection, namespace: &str, password: &str) -> VaultResult<Option<crypto::SecretKey>> {
+    let stored = stored_key(conn, namespace)?.ok_or_else(|| {
+        VaultError::InvalidArgument(format!("namespace {} has no password of its own", namespace))
+    })?;
+    throttle_unlock(conn, namespace, || stored.derive(password))
+}
+
+/// Unlock a namespace that has its own password, returning false if the
+/// password is wrong. Repeated wrong passwords lock it out the way `unlock`
+/// does, counted separately from the master password. It stays unlocked
+/// until `lock_namespace` or `lock`.
+#[napi]
+pub fn unlock_namespace(namespace: String, password: String) -> napi::Result<bool, ErrorCode> {
+    let password = Zeroizing::new(password);
+    let namespace = normalize_namespace(&namespace);
+
+    let unlocked = with_conn(|conn| {
+        let Some(key) = attempt_namespace_password(conn, &namespace, &password)? else {
+            return Ok(false);
+        };
+        cache_key(&namespace, key);
+        Ok(true)
+    })?;
+
+    Ok(unlocked)
+}
+
+/// Lock a namespace that has its own password again, wiping its key
+#[napi]
+pub fn lock_namespace(namespace: String) {
+    if let Ok(mut keys) = NAMESPACE_KEYS.lock() {
+        keys.remove(&normalize_namespace(&namespace));
+    }
+}
+
+/// Remove a namespace's own password, re-encrypting its secrets and their
+/// history under the vault key. `password` must be the namespace's current
+/// one. Fails with `Locked` while the vault is locked.
+#[napi]
+pub fn remove_namespace_password(namespace: String, password: String) -> napi::Result<(), ErrorCode> {
+    let password = Zeroizing::new(password);
+    let namespace = normalize_namespace(&namespace);
+
+    with_conn(|conn| {
+        let vault_key = require_key(conn)?;
+        let key = attempt_namespace_password(conn, &namespace, &password)?
+            .ok_or_else(|| VaultError::InvalidArgument("namespace password is incorrect".to_string()))?;
+
+        let tx = conn.transaction()?;
+        reencrypt_where(&tx, "namespace = ?1", params![namespace], &key, &vault_key)?;
+        tx.execute("DELETE FROM namespace_keys WHERE namespace = ?1", params![namespace])?;
+        tx.commit()?;
+
+        lock_namespace(namespace.clone());
+        Ok(())
+    })?;
+
+    crate::undo::forget();
+    Ok(())
+}
+
+/// Whether `namespace` is protected by a password of its own
+#[napi]
+pub fn namespace_has_password(namespace: String) -> napi::Result<bool, ErrorCode> {
+    let has_password = with_conn(|conn| has_own_key(conn, &normalize_namespace(&namespace)))?;
+    Ok(has_password)
+}
+
+/// Whether `namespace` has its own password and hasn't been unlocked
+#[napi]
+pub fn is_namespace_locked(namespace: String) -> napi::Result<bool, ErrorCode> {
+    let namespace = normalize_namespace(&namespace);
+    let locked = with_conn(|conn| Ok(has_own_key(conn, &namespace)? && cached_key(&namespace).is_none()))?;
+    Ok(locked)
+}
@@ -0,0 +1,415 @@
+use crate::error::{ErrorCode, VaultError, VaultResult};
+use crate::namespace_keys::VaultKeys;
+use crate::tags::normalize_tag;
+use crate::{audit_log, files, get_decrypted_pairs, normalize_namespace, open_value, with_conn, DEFAULT_NAMESPACE};
+use napi_derive::napi;
+use rusqlite::{params, Connection};
+use std::path::PathBuf;
+use zeroize::Zeroizing;
+
+/// Sentinel comments around the block EnvVault manages in shell profiles
+const BLOCK_START: &str = "# >>> envvault >>>";
+const BLOCK_END: &str = "# <<< envvault <<<";
+
+/// Shells `sync_to_shell` knows how to write for
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ShellKind {
+    /// bash and zsh together, the behaviour when no shell is given
+    Posix,
+    Bash,
+    Zsh,
+    Fish,
+    PowerShell,
+}
+
+impl ShellKind {
+    /// Parse a shell name. Without one, Windows gets PowerShell and
+    /// everything else keeps the original bash + zsh behaviour.
+    pub(crate) fn parse(name: Option<&str>) -> VaultResult<Self> {
+        match name.map(|name| name.trim().to_lowercase()).as_deref() {
+            None | Some("") if cfg!(windows) => Ok(ShellKind::PowerShell),
+            None | Some("") | Some("sh") => Ok(ShellKind::Posix),
+            Some("bash") => Ok(ShellKind::Bash),
+            Some("zsh") => Ok(ShellKind::Zsh),
+            Some("fish") => Ok(ShellKind::Fish),
+            Some("powershell") | Some("pwsh") => Ok(ShellKind::PowerShell),
+            Some(other) => Err(VaultError::InvalidArgument(format!("unsupported shell {}", other))),
+        }
+    }
+
+    /// File name of the generated script in the home directory
+    fn script_name(self) -> &'static str {
+        match self {
+            ShellKind::Posix | ShellKind::Bash | ShellKind::Zsh => ".envvault",
+            ShellKind::Fish => ".envvault.fish",
+            ShellKind::PowerShell => ".envvault.ps1",
+        }
+    }
+
+    /// Lines added to a profile so new shells load the script
+    fn source_line(self) -> &'static str {
+        match self {
+            ShellKind::Posix | ShellKind::Bash | ShellKind::Zsh => "[ -f ~/.envvault ] && source ~/.envvault",
+            ShellKind::Fish => "test -f ~/.envvault.fish; and source ~/.envvault.fish",
+            ShellKind::PowerShell => "if (Test-Path \"$HOME/.envvault.ps1\") { . \"$HOME/.envvault.ps1\" }",
+        }
+    }
+
+    /// The sentinel-delimited block kept in a profile
+    fn source_block(self) -> String {
+        format!("{}\n{}\n{}\n", BLOCK_START, self.source_line(), BLOCK_END)
+    }
+
+    /// The block older versions appended without sentinels
+    fn legacy_block(self) -> String {
+        format!("\n# EnvVault secrets\n{}\n", self.source_line())
+    }
+
+    /// Profile files the source line goes into, and whether a missing
+    /// profile should be created rather than skipped
+    fn profiles(self) -> (Vec<PathBuf>, bool) {
+        let Some(home) = dirs::home_dir() else {
+            return (vec![], false);
+        };
+
+        match self {
+            ShellKind::Posix => (vec![home.join(".zshrc"), home.join(".bashrc"), home.join(".bash_profile")], false),
+            ShellKind::Bash => (vec![home.join(".bashrc"), home.join(".bash_profile")], false),
+            ShellKind::Zsh => (vec![home.join(".zshrc")], false),
+            ShellKind::Fish => (vec![home.join(".config").join("fish").join("config.fish")], true),
+            ShellKind::PowerShell => {
+                let profiles = if cfg!(windows) {
+                    let documents = dirs::document_dir().unwrap_or_else(|| home.join("Documents"));
+                    vec![
+                        documents.join("PowerShell").join("Microsoft.PowerShell_profile.ps1"),
+                        documents.join("WindowsPowerShell").join("Microsoft.PowerShell_profile.ps1"),
+                    ]
+                } else {
+                    vec![home.join(".config").join("powershell").join("Microsoft.PowerShell_profile.ps1")]
+                };
+                (profiles, true)
+            }
+        }
+    }
+}
+
+/// Render `(key, value)` pairs as statements that set them in `shell`.
+/// Values are quoted so the shell never expands anything inside them.
+pub(crate) fn render_exports(shell: ShellKind, pairs: &[(String, Zeroizing<String>)]) -> Zeroizing<String> {
+    // Sized up front so the buffer never reallocates and leaves copies behind
+    let capacity = pairs.iter().map(|(k, v)| k.len() + v.len() * 2 + 16).sum();
+    let mut out = Zeroizing::new(String::with_capacity(capacity));
+
+    for (i, (k, v)) in pairs.iter().enumerate() {
+        if i > 0 {
+            out.push('\n');
+        }
+        let (prefix, open, escapes, escape_char): (&str, char, &[char], char) = match shell {
+            ShellKind::Posix | ShellKind::Bash | ShellKind::Zsh => ("export ", '"', &['\\', '"', '$', '`'], '\\'),
+            ShellKind::Fish => ("set -gx ", '\'', &['\\', '\''], '\\'),
+            ShellKind::PowerShell => ("$env:", '\'', &['\''], '\''),
+        };
+        let separator = match shell {
+            ShellKind::Posix | ShellKind::Bash | ShellKind::Zsh => "=",
+            ShellKind::Fish => " ",
+            ShellKind::PowerShell => " = ",
+        };
+
+        out.push_str(prefix);
+        out.push_str(k);
+        out.push_str(separator);
+        out.push(open);
+        for c in v.chars() {
+            if escapes.contains(&c) {
+                out.push(escape_char);
+            }
+            out.push(c);
+        }
+        out.push(open);
+    }
+
+    out
+}
+
+/// Byte range of the first sentinel block in `content`, including one
+/// trailing newline
+fn find_block(content: &str) -> Option<(usize, usize)> {
+    let start = content.find(BLOCK_START)?;
+    let mut end = start + content[start..].find(BLOCK_END)? + BLOCK_END.len();
+    if content[end..].starts_with('\n') {
+        end += 1;
+    }
+    Some((start, end))
+}
+
+/// Remove every sentinel block from `content`, along with the newline
+/// `install_block` put in front of it, and any legacy block
+fn remove_blocks(content: &str, legacy_block: &str) -> String {
+    let mut content = content.replace(legacy_block, "");
+    while let Some((mut start, end)) = find_block(&content) {
+        if content[..start].ends_with('\n') {
+            start -= 1;
+        }
+        content.replace_range(start..end, "");
+    }
+    content
+}
+
+/// Put `block` into a profile's `content`: replace an existing sentinel
+/// block in place, drop any duplicates, or append it with a blank line
+fn install_block(content: &str, block: &str, legacy_block: &str) -> String {
+    match find_block(content) {
+        Some((start, end)) => {
+            let rest = remove_blocks(&content[end..], legacy_block);
+            format!("{}{}{}", &content[..start], block, rest)
+        }
+        None if content.contains(legacy_block) => {
+            content.replacen(legacy_block, &format!("\n{}", block), 1).replace(legacy_block, "")
+        }
+        None => format!("{}\n{}", content, block),
+    }
+}
+
+/// Get the path to the generated script for `shell`
+fn script_path(shell: ShellKind) -> PathBuf {
+    if let Some(home) = dirs::home_dir() {
+        home.join(shell.script_name())
+    } else {
+        PathBuf::from(shell.script_name())
+    }
+}
+
+/// A profile a sync would touch, with its current and updated contents
+struct ProfilePlan {
+    path: PathBuf,
+    exists: bool,
+    current: String,
+    updated: String,
+}
+
+/// Work out what a sync does to each of the shell's profiles, keeping
+/// exactly one source block in each. Missing profiles are left out unless
+/// the shell wants them created. Fails if an existing profile can't be
+/// read, rather than planning to overwrite it.
+fn plan_profiles(shell: ShellKind) -> VaultResult<Vec<ProfilePlan>> {
+    let source_block = shell.source_block();
+    let legacy_block = shell.legacy_block();
+    let (profiles, create_missing) = shell.profiles();
+
+    profiles
+        .into_iter()
+        .filter(|path| create_missing || path.exists())
+        .map(|path| {
+            let exists = path.exists();
+            let current = match std::fs::read_to_string(&path) {
+                Ok(current) => current,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+                Err(e) => return Err(VaultError::io_at(&path.to_string_lossy(), e)),
+            };
+            let updated = install_block(&current, &source_block, &legacy_block);
+            Ok(ProfilePlan { path, exists, current, updated })
+        })
+        .collect()
+}
+
+/// Write `content` to the script for `shell` and source it from the
+/// shell's profiles
+fn write_shell_sync(shell: ShellKind, content: &str) -> VaultResult<()> {
+    // The script holds plaintext secrets, so keep it owner-only
+    let path = script_path(shell);
+    files::write_private_atomic(&path, content.as_bytes())
+        .map_err(|e| VaultError::io_at(&path.to_string_lossy(), e))?;
+
+    for plan in plan_profiles(shell)?.into_iter().filter(|plan| plan.updated != plan.current) {
+        let display = plan.path.to_string_lossy();
+        if let Some(parent) = plan.path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| VaultError::io_at(&display, e))?;
+        }
+        std::fs::write(&plan.path, &plan.updated).map_err(|e| VaultError::io_at(&display, e))?;
+    }
+
+    Ok(())
+}
+
+/// A shell profile as described by `sync_to_shell_preview`
+#[napi(object)]
+pub struct ProfilePreview {
+    pub path: String,
+    /// Whether the file exists yet; fish and PowerShell profiles are created
+    pub exists: bool,
+    /// Whether the profile already sources the script
+    pub has_source_line: bool,
+    /// Whether the sync would change the file
+    pub will_modify: bool,
+}
+
+/// What `sync_to_shell` would write, as returned to JavaScript
+#[napi(object)]
+pub struct SyncPreview {
+    pub script_path: String,
+    /// Contents the script would be given, including plaintext values
+    pub script_contents: String,
+    pub profiles: Vec<ProfilePreview>,
+}
+
+/// Show what `sync_to_shell` would do for `shell` without writing anything:
+/// the script it would generate and the profiles it would edit
+#[napi]
+pub fn sync_to_shell_preview(shell: Option<String>) -> napi::Result<SyncPreview, ErrorCode> {
+    let shell = ShellKind::parse(shell.as_deref())?;
+    let rows = with_conn(|conn| get_decrypted_pairs(conn, DEFAULT_NAMESPACE, false))?;
+
+    let profiles = plan_profiles(shell)?
+        .into_iter()
+        .map(|plan| ProfilePreview {
+            path: plan.path.to_string_lossy().to_string(),
+            exists: plan.exists,
+            has_source_line: plan.current.contains(shell.source_line()),
+            will_modify: plan.updated != plan.current,
+        })
+        .collect();
+
+    Ok(SyncPreview {
+        script_path: script_path(shell).to_string_lossy().to_string(),
+        script_contents: render_exports(shell, &rows).to_string(),
+        profiles,
+    })
+}
+
+/// Decrypt the secrets in `namespace` (any namespace when `None`) that
+/// carry every tag in `tags`, ordered by namespace then key
+fn get_filtered_pairs(conn: &Connection, namespace: Option<&str>, tags: &[String]) -> VaultResult<Vec<(String, Zeroizing<String>)>> {
+    let keys = VaultKeys::load(conn)?;
+    let tags_json = serde_json::to_string(tags)
+        .map_err(|e| VaultError::InvalidArgument(e.to_string()))?;
+
+    let mut stmt = conn.prepare(
+        "SELECT key, value, nonce, namespace, compressed FROM secrets
+         WHERE (?1 IS NULL OR namespace = ?1) AND NOT binary
+           AND (SELECT COUNT(*) FROM secret_tags st JOIN tags t ON t.id = st.tag_id
+                WHERE st.secret_id = secrets.id AND t.name IN (SELECT value FROM json_each(?2))) = ?3
+         ORDER BY namespace ASC, key ASC",
+    )?;
+
+    let rows = stmt
+        .query_map(params![namespace, tags_json, tags.len() as i64], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, bool>(4)?,
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let pairs = rows
+        .into_iter()
+        .map(|(k, v, n, namespace, compressed)| {
+            let value = open_value(keys.require(&namespace)?, &v, &n, compressed)
+                .ok_or_else(|| VaultError::Crypto(format!("failed to decrypt {}", k)))?;
+            Ok((k, value))
+        })
+        .collect::<VaultResult<_>>()?;
+    audit_log::record_vault(conn, "export", namespace)?;
+    Ok(pairs)
+}
+
+/// Describe a sync filter for the generated script's header
+fn filter_description(namespace: Option<&str>, tags: &[String]) -> String {
+    let namespace = match namespace {
+        Some(ns) => format!("namespace {}", ns),
+        None => "all namespaces".to_string(),
+    };
+    if tags.is_empty() {
+        namespace
+    } else {
+        format!("{}, tags {}", namespace, tags.join(", "))
+    }
+}
+
+/// Sync all secrets in the default namespace to a script in the home
+/// directory and source it from the shell's profiles.
+/// `shell` may be `"bash"`, `"zsh"`, `"fish"` or `"powershell"`; when omitted
+/// both bash and zsh profiles are updated (PowerShell on Windows).
+#[napi]
+pub fn sync_to_shell(shell: Option<String>) -> napi::Result<(), ErrorCode> {
+    let shell = ShellKind::parse(shell.as_deref())?;
+    let rows = with_conn(|conn| get_decrypted_pairs(conn, DEFAULT_NAMESPACE, false))?;
+
+    write_shell_sync(shell, &render_exports(shell, &rows))?;
+    Ok(())
+}
+
+/// Sync only the secrets in `namespace` that carry every tag in `tags`.
+/// Without a namespace, matching secrets from all namespaces are written;
+/// at least one of the two filters is required. The script starts with a
+/// comment naming the filter that produced it.
+#[napi]
+pub fn sync_to_shell_filtered(namespace: Option<String>, tags: Vec<String>, shell: Option<String>) -> napi::Result<(), ErrorCode> {
+    let shell = ShellKind::parse(shell.as_deref())?;
+    let namespace = namespace.map(|ns| normalize_namespace(&ns));
+    let mut tags = tags
+        .iter()
+        .map(|tag| normalize_tag(tag))
+        .collect::<VaultResult<Vec<_>>>()?;
+    tags.sort();
+    tags.dedup();
+
+    if namespace.is_none() && tags.is_empty() {
+        return Err(VaultError::InvalidArgument("a namespace or at least one tag is required".to_string()).into());
+    }
+
+    let rows = with_conn(|conn| get_filtered_pairs(conn, namespace.as_deref(), &tags))?;
+    let header = format!("# EnvVault secrets synced for {}", filter_description(namespace.as_deref(), &tags));
+    let exports = render_exports(shell, &rows);
+    let content = if exports.is_empty() {
+        Zeroizing::new(header)
+    } else {
+        Zeroizing::new(format!("{}\n{}", header, *exports))
+    };
+
+    write_shell_sync(shell, &content)?;
+    Ok(())
+}
+
+/// Remove the blocks `sync_to_shell` added to every bash, zsh, fish and
+/// PowerShell profile, leaving the rest of each profile untouched.
+/// With `delete_scripts`, the generated scripts are deleted as well.
+#[napi]
+pub fn unsync_from_shell(delete_scripts: Option<bool>) -> napi::Result<(), ErrorCode> {
+    for shell in [ShellKind::Posix, ShellKind::Fish, ShellKind::PowerShell] {
+        let legacy_block = shell.legacy_block();
+
+        for profile_path in shell.profiles().0 {
+            let display = profile_path.to_string_lossy().to_string();
+            let content = match std::fs::read_to_string(&profile_path) {
+                Ok(content) => content,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(VaultError::io_at(&display, e).into()),
+            };
+
+            let updated = remove_blocks(&content, &legacy_block);
+            if updated != content {
+                std::fs::write(&profile_path, updated).map_err(|e| VaultError::io_at(&display, e))?;
+            }
+        }
+
+        if delete_scripts.unwrap_or(false) {
+            let path = script_path(shell);
+            match std::fs::remove_file(&path) {
+                Ok(()) => {}
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => return Err(VaultError::io_at(&path.to_string_lossy(), e).into()),
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Get the envvault file path for display
+#[napi]
+pub fn get_envvault_file_path() -> String {
+    script_path(ShellKind::Posix).to_string_lossy().to_string()
+}
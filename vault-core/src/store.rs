@@ -0,0 +1,126 @@
+//! The basic operations on stored secrets: insert, get, list, search,
+//! delete and history, implemented here for SQLite. In the crate root,
+//! `upsert_secret`, `update_secret`, `revert_secret`, `delete_secret`, key
+//! lookups, search ranking and key suggestions use `SecretStore`.
+//!
+//! The `#[napi]` functions are not yet adapters over a boxed store, and
+//! there is no in-memory implementation. Most of them also rely on SQLite
+//! transactions, triggers, tags, aliases and audit rows that the trait
+//! doesn't cover, so they still take the connection from `with_conn`.
+
+use crate::error::{VaultError, VaultResult};
+use crate::{history_limit, Matching};
+use rusqlite::{params, Connection, OptionalExtension};
+
+/// A value as it is stored: encrypted, with what's needed to decrypt it
+pub struct StoredValue {
+    pub ciphertext: String,
+    pub nonce: String,
+    /// Whether the plaintext was compressed before it was encrypted
+    pub compressed: bool,
+}
+
+/// One secret as it is stored
+pub struct StoredSecret {
+    pub id: u32,
+    pub value: StoredValue,
+}
+
+/// Operations on stored secrets. Values go in and come out encrypted;
+/// keys are compared exactly unless a method says otherwise.
+pub trait SecretStore {
+    /// Store `value` under `key` in `namespace`. Overwriting an existing
+    /// key keeps its id and creation time and records the previous value
+    /// in its history.
+    fn insert(&self, namespace: &str, key: &str, value: &StoredValue) -> VaultResult<()>;
+
+    /// The secret stored under `key` in `namespace`, or with `key` as one
+    /// of its aliases, if any
+    fn get(&self, namespace: &str, key: &str) -> VaultResult<Option<StoredSecret>>;
+
+    /// `(id, key)` of every secret in `namespace`, ordered by key
+    fn list(&self, namespace: &str) -> VaultResult<Vec<(u32, String)>>;
+
+    /// `(id, key)` of the secrets in `namespace` matching `query` as
+    /// `matching` describes, in no particular order
+    fn search(&self, namespace: &str, query: &str, matching: Matching) -> VaultResult<Vec<(u32, String)>>;
+
+    /// Delete secret `id`, returning whether it existed
+    fn delete(&self, id: u32) -> VaultResult<bool>;
+
+    /// Copy secret `id`'s current value, still encrypted, into its history,
+    /// dropping the oldest entries beyond the configured history limit
+    fn record_history(&self, id: u32) -> VaultResult<()>;
+}
+
+impl SecretStore for Connection {
+    fn insert(&self, namespace: &str, key: &str, value: &StoredValue) -> VaultResult<()> {
+        let existing: Option<u32> = self
+            .query_row(
+                "SELECT id FROM secrets WHERE namespace = ?1 AND key = ?2",
+                params![namespace, key],
+                |row| row.get(0),
+            )
+            .optional()?;
+        if let Some(id) = existing {
+            self.record_history(id)?;
+        }
+
+        self.execute(
+            "INSERT INTO secrets (namespace, key, value, nonce, compressed) VALUES (?1, ?2, ?3, ?4, ?5)
+             ON CONFLICT (namespace, key) DO UPDATE SET
+                value = excluded.value, nonce = excluded.nonce, compressed = excluded.compressed, binary = 0,
+                updated_at = CURRENT_TIMESTAMP",
+            params![namespace, key, value.ciphertext, value.nonce, value.compressed],
+        )
+        .map_err(|err| VaultError::from_write(err, key))?;
+        Ok(())
+    }
+
+    fn get(&self, namespace: &str, key: &str) -> VaultResult<Option<StoredSecret>> {
+        Ok(self
+            .query_row(
+                "SELECT id, value, nonce, compressed FROM secrets WHERE namespace = ?1
+                 AND (key = ?2 OR id IN (SELECT secret_id FROM secret_aliases WHERE alias = ?2))",
+                params![namespace, key],
+                |row| {
+                    Ok(StoredSecret {
+                        id: row.get(0)?,
+                        value: StoredValue { ciphertext: row.get(1)?, nonce: row.get(2)?, compressed: row.get(3)? },
+                    })
+                },
+            )
+            .optional()?)
+    }
+
+    fn list(&self, namespace: &str) -> VaultResult<Vec<(u32, String)>> {
+        let mut stmt = self.prepare("SELECT id, key FROM secrets WHERE namespace = ?1 ORDER BY key ASC")?;
+        let rows = stmt.query_map(params![namespace], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    fn search(&self, namespace: &str, query: &str, matching: Matching) -> VaultResult<Vec<(u32, String)>> {
+        let mut stmt = self.prepare(&format!("SELECT id, key FROM secrets WHERE {}", matching.filter()))?;
+        let rows = stmt.query_map(params![namespace, matching.pattern(query)], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    fn delete(&self, id: u32) -> VaultResult<bool> {
+        Ok(self.execute("DELETE FROM secrets WHERE id = ?1", params![id])? > 0)
+    }
+
+    fn record_history(&self, id: u32) -> VaultResult<()> {
+        self.execute(
+            "INSERT INTO secret_history (secret_id, value, nonce, compressed, binary)
+             SELECT id, value, nonce, compressed, binary FROM secrets WHERE id = ?1",
+            params![id],
+        )?;
+        self.execute(
+            "DELETE FROM secret_history WHERE secret_id = ?1 AND id NOT IN (
+                SELECT id FROM secret_history WHERE secret_id = ?1 ORDER BY id DESC LIMIT ?2
+            )",
+            params![id, history_limit(self)],
+        )?;
+        Ok(())
+    }
+}
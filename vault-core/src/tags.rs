@@ -0,0 +1,187 @@
+use crate::detect::{detect, SecretType};
+use crate::error::{ErrorCode, VaultError, VaultResult};
+use crate::namespace_keys::VaultKeys;
+use crate::{
+    delete_matching, into_text, mask_style, open_secret, secret_item_from_row, with_conn, BulkDeleteReport, SecretItem,
+    SECRET_ITEM_COLUMNS,
+};
+use napi_derive::napi;
+use rusqlite::{params, Connection};
+
+/// Separator used when tags are aggregated into one column
+pub(crate) const TAG_SEPARATOR: char = '\u{1f}';
+
+/// Trim and lowercase a tag so lookups are case-insensitive
+pub(crate) fn normalize_tag(tag: &str) -> VaultResult<String> {
+    let tag = tag.trim().to_lowercase();
+    if tag.is_empty() || tag.contains(TAG_SEPARATOR) {
+        return Err(VaultError::InvalidArgument(format!("invalid tag {:?}", tag)));
+    }
+    Ok(tag)
+}
+
+/// Fail with `NotFound` unless a secret with `id` exists
+fn require_secret(conn: &Connection, id: u32) -> VaultResult<()> {
+    let exists = conn
+        .prepare("SELECT 1 FROM secrets WHERE id = ?1")?
+        .exists(params![id])?;
+    if !exists {
+        return Err(VaultError::NotFound(id));
+    }
+    Ok(())
+}
+
+/// Split the aggregated `tags` column back into a sorted list
+pub(crate) fn split_tags(tags: Option<String>) -> Vec<String> {
+    let mut tags: Vec<String> = tags
+        .map(|tags| tags.split(TAG_SEPARATOR).map(str::to_string).collect())
+        .unwrap_or_default();
+    tags.sort();
+    tags
+}
+
+/// Tag a secret
+#[napi]
+pub fn add_tag(id: u32, tag: String) -> napi::Result<(), ErrorCode> {
+    let tag = normalize_tag(&tag)?;
+    with_conn(|conn| {
+        require_secret(conn, id)?;
+        conn.execute("INSERT OR IGNORE INTO tags (name) VALUES (?1)", params![tag])?;
+        conn.execute(
+            "INSERT OR IGNORE INTO secret_tags (secret_id, tag_id) SELECT ?1, id FROM tags WHERE name = ?2",
+            params![id, tag],
+        )?;
+        Ok(())
+    })?;
+
+    Ok(())
+}
+
+/// Detect what kind of credential a secret holds and tag it accordingly,
+/// e.g. `jwt` or `private-key`. Generic values are left untagged.
+#[napi]
+pub fn tag_secret_type(id: u32) -> napi::Result<SecretType, ErrorCode> {
+    let secret_type = with_conn(|conn| {
+        let value = into_text(open_secret(conn, id)?)
+            .ok_or_else(|| VaultError::Crypto(format!("failed to decrypt secret {}", id)))?;
+
+        let secret_type = detect(&value);
+        if let Some(tag) = secret_type.tag() {
+            conn.execute("INSERT OR IGNORE INTO tags (name) VALUES (?1)", params![tag])?;
+            conn.execute(
+                "INSERT OR IGNORE INTO secret_tags (secret_id, tag_id) SELECT ?1, id FROM tags WHERE name = ?2",
+                params![id, tag],
+            )?;
+        }
+        Ok(secret_type)
+    })?;
+
+    Ok(secret_type)
+}
+
+/// Remove a tag from a secret
+#[napi]
+pub fn remove_tag(id: u32, tag: String) -> napi::Result<(), ErrorCode> {
+    let tag = normalize_tag(&tag)?;
+    with_conn(|conn| {
+        require_secret(conn, id)?;
+        conn.execute(
+            "DELETE FROM secret_tags WHERE secret_id = ?1 AND tag_id IN (SELECT id FROM tags WHERE name = ?2)",
+            params![id, tag],
+        )?;
+        // Drop the tag itself once nothing uses it
+        conn.execute(
+            "DELETE FROM tags WHERE name = ?1 AND id NOT IN (SELECT tag_id FROM secret_tags)",
+            params![tag],
+        )?;
+        Ok(())
+    })?;
+
+    Ok(())
+}
+
+/// Replace a secret's tags with `tags`, which are normalized and
+/// deduplicated. Only tags that actually change are added or removed.
+#[napi]
+pub fn set_tags(id: u32, tags: Vec<String>) -> napi::Result<(), ErrorCode> {
+    let mut tags = tags.iter().map(|tag| normalize_tag(tag)).collect::<VaultResult<Vec<_>>>()?;
+    tags.sort();
+    tags.dedup();
+
+    with_conn(|conn| {
+        let tx = conn.transaction()?;
+        require_secret(&tx, id)?;
+        let current: Vec<String> = tx
+            .prepare("SELECT t.name FROM tags t JOIN secret_tags st ON st.tag_id = t.id WHERE st.secret_id = ?1")?
+            .query_map(params![id], |row| row.get(0))?
+            .collect::<Result<_, _>>()?;
+
+        for tag in current.iter().filter(|tag| !tags.contains(tag)) {
+            tx.execute(
+                "DELETE FROM secret_tags WHERE secret_id = ?1 AND tag_id IN (SELECT id FROM tags WHERE name = ?2)",
+                params![id, tag],
+            )?;
+            tx.execute(
+                "DELETE FROM tags WHERE name = ?1 AND id NOT IN (SELECT tag_id FROM secret_tags)",
+                params![tag],
+            )?;
+        }
+        for tag in tags.iter().filter(|tag| !current.contains(tag)) {
+            tx.execute("INSERT OR IGNORE INTO tags (name) VALUES (?1)", params![tag])?;
+            tx.execute(
+                "INSERT INTO secret_tags (secret_id, tag_id) SELECT ?1, id FROM tags WHERE name = ?2",
+                params![id, tag],
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    })?;
+
+    Ok(())
+}
+
+/// List every tag in use, alphabetically
+#[napi]
+pub fn list_tags() -> napi::Result<Vec<String>, ErrorCode> {
+    let tags = with_conn(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT DISTINCT t.name FROM tags t JOIN secret_tags st ON st.tag_id = t.id ORDER BY t.name ASC",
+        )?;
+        let rows = stmt.query_map([], |row| row.get(0))?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    })?;
+
+    Ok(tags)
+}
+
+/// Get every secret carrying a tag, across all namespaces
+#[napi]
+pub fn search_by_tag(tag: String) -> napi::Result<Vec<SecretItem>, ErrorCode> {
+    let tag = normalize_tag(&tag)?;
+    let items = with_conn(|conn| {
+        let keys = VaultKeys::load(conn)?;
+        let style = mask_style(conn);
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM secrets WHERE id IN (
+                SELECT st.secret_id FROM secret_tags st JOIN tags t ON t.id = st.tag_id WHERE t.name = ?1
+            ) ORDER BY namespace ASC, key ASC",
+            SECRET_ITEM_COLUMNS
+        ))?;
+
+        let rows = stmt.query_map(params![tag], |row| secret_item_from_row(&keys, style, row))?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    })?;
+
+    Ok(items)
+}
+
+/// Delete every secret carrying a tag, across all namespaces. Call with
+/// `confirm` false first to see which keys would go; only a call with
+/// `confirm` set deletes anything. Fails with `ReadOnly` if a match is
+/// read-only, unless `force` is set.
+#[napi]
+pub fn delete_by_tag(tag: String, confirm: bool, force: Option<bool>) -> napi::Result<BulkDeleteReport, ErrorCode> {
+    let tag = normalize_tag(&tag)?;
+    let condition = "id IN (SELECT st.secret_id FROM secret_tags st JOIN tags t ON t.id = st.tag_id WHERE t.name = ?1)";
+    Ok(with_conn(|conn| delete_matching(conn, condition, params![tag], confirm, force))?)
+}
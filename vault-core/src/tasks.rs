@@ -0,0 +1,90 @@
+use crate::error::{task_error, ErrorCode};
+use crate::{change_master_password, import_from_env_string, import_from_file};
+use napi::{bindgen_prelude::AsyncTask, Env, Task};
+use napi_derive::napi;
+use zeroize::Zeroizing;
+
+/// Worker for `import_from_env_string_async`
+pub struct ImportEnvStringTask {
+    content: Zeroizing<String>,
+}
+
+#[napi]
+impl Task for ImportEnvStringTask {
+    // Failures are carried to `resolve` so they reach JavaScript with their code
+    type Output = napi::Result<u32, ErrorCode>;
+    type JsValue = u32;
+
+    fn compute(&mut self) -> napi::Result<Self::Output> {
+        Ok(import_from_env_string(std::mem::take(&mut *self.content)))
+    }
+
+    fn resolve(&mut self, env: Env, imported: Self::Output) -> napi::Result<u32> {
+        imported.map_err(|err| task_error(env, err))
+    }
+}
+
+/// `import_from_env_string` on a worker thread, so the event loop stays
+/// free; resolves to the number of secrets imported. Other vault calls
+/// wait on the shared connection until the import is done with it.
+#[napi]
+pub fn import_from_env_string_async(content: String) -> AsyncTask<ImportEnvStringTask> {
+    AsyncTask::new(ImportEnvStringTask { content: Zeroizing::new(content) })
+}
+
+/// Worker for `import_from_file_async`
+pub struct ImportFileTask {
+    path: String,
+}
+
+#[napi]
+impl Task for ImportFileTask {
+    // Failures are carried to `resolve` so they reach JavaScript with their code
+    type Output = napi::Result<u32, ErrorCode>;
+    type JsValue = u32;
+
+    fn compute(&mut self) -> napi::Result<Self::Output> {
+        Ok(import_from_file(std::mem::take(&mut self.path)))
+    }
+
+    fn resolve(&mut self, env: Env, imported: Self::Output) -> napi::Result<u32> {
+        imported.map_err(|err| task_error(env, err))
+    }
+}
+
+/// `import_from_file` off the main thread; resolves to the number of
+/// secrets imported
+#[napi]
+pub fn import_from_file_async(path: String) -> AsyncTask<ImportFileTask> {
+    AsyncTask::new(ImportFileTask { path })
+}
+
+/// Worker for `change_master_password_async`
+pub struct ChangeMasterPasswordTask {
+    old: Zeroizing<String>,
+    new: Zeroizing<String>,
+}
+
+#[napi]
+impl Task for ChangeMasterPasswordTask {
+    type Output = napi::Result<(), ErrorCode>;
+    type JsValue = ();
+
+    fn compute(&mut self) -> napi::Result<Self::Output> {
+        Ok(change_master_password(std::mem::take(&mut *self.old), std::mem::take(&mut *self.new)))
+    }
+
+    fn resolve(&mut self, env: Env, changed: Self::Output) -> napi::Result<()> {
+        changed.map_err(|err| task_error(env, err))
+    }
+}
+
+/// `change_master_password` off the main thread, covering both the key
+/// derivation and re-encrypting every secret
+#[napi]
+pub fn change_master_password_async(old: String, new: String) -> AsyncTask<ChangeMasterPasswordTask> {
+    AsyncTask::new(ChangeMasterPasswordTask {
+        old: Zeroizing::new(old),
+        new: Zeroizing::new(new),
+    })
+}
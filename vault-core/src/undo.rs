@@ -0,0 +1,152 @@
+use crate::audit_log;
+use crate::error::{ErrorCode, VaultError, VaultResult};
+use crate::with_conn;
+use napi_derive::napi;
+use rusqlite::types::Value;
+use rusqlite::{params, Connection};
+use std::sync::Mutex;
+
+/// Rows of one table exactly as stored, so they can be put back unchanged
+struct TableRows {
+    columns: Vec<String>,
+    rows: Vec<Vec<Value>>,
+}
+
+impl TableRows {
+    /// Read every row `select` returns, with all its columns
+    fn read(conn: &Connection, select: &str, args: &[&dyn rusqlite::ToSql]) -> VaultResult<Self> {
+        let mut stmt = conn.prepare(select)?;
+        let columns: Vec<String> = stmt.column_names().into_iter().map(str::to_string).collect();
+        let rows = stmt
+            .query_map(args, |row| (0..columns.len()).map(|i| row.get::<_, Value>(i)).collect())?
+            .collect::<Result<_, _>>()?;
+        Ok(TableRows { columns, rows })
+    }
+
+    /// Insert the rows back into `table`
+    fn restore(&self, conn: &Connection, table: &str) -> VaultResult<()> {
+        let placeholders: Vec<String> = (1..=self.columns.len()).map(|i| format!("?{}", i)).collect();
+        let mut stmt = conn.prepare(&format!(
+            "INSERT INTO {} ({}) VALUES ({})",
+            table,
+            self.columns.join(", "),
+            placeholders.join(", ")
+        ))?;
+        for row in &self.rows {
+            stmt.execute(rusqlite::params_from_iter(row))?;
+        }
+        Ok(())
+    }
+}
+
+/// Everything a delete removed: the secret rows with their values still
+/// encrypted, their history and aliases, and the names of their tags
+pub(crate) struct DeletedSecrets {
+    /// `(id, namespace, key)` of each secret
+    secrets: Vec<(u32, String, String)>,
+    rows: TableRows,
+    history: TableRows,
+    aliases: TableRows,
+    tags: Vec<(u32, String)>,
+}
+
+/// The most recent delete, kept until it is undone, replaced by another
+/// delete, or made stale by the vault's keys changing
+static LAST_DELETE: Mutex<Option<DeletedSecrets>> = Mutex::new(None);
+
+/// Snapshot the secrets matching `condition` (an SQL expression over
+/// `secrets` taking `args`) just before they are deleted
+pub(crate) fn capture(conn: &Connection, condition: &str, args: &[&dyn rusqlite::ToSql]) -> VaultResult<DeletedSecrets> {
+    let secrets = conn
+        .prepare(&format!("SELECT id, namespace, key FROM secrets WHERE {}", condition))?
+        .query_map(args, |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+        .collect::<Result<_, _>>()?;
+    let tags = conn
+        .prepare(&format!(
+            "SELECT st.secret_id, t.name FROM secret_tags st JOIN tags t ON t.id = st.tag_id
+             WHERE st.secret_id IN (SELECT id FROM secrets WHERE {})",
+            condition
+        ))?
+        .query_map(args, |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<_, _>>()?;
+
+    Ok(DeletedSecrets {
+        secrets,
+        rows: TableRows::read(conn, &format!("SELECT * FROM secrets WHERE {}", condition), args)?,
+        history: TableRows::read(
+            conn,
+            &format!("SELECT * FROM secret_history WHERE secret_id IN (SELECT id FROM secrets WHERE {})", condition),
+            args,
+        )?,
+        aliases: TableRows::read(
+            conn,
+            &format!("SELECT * FROM secret_aliases WHERE secret_id IN (SELECT id FROM secrets WHERE {})", condition),
+            args,
+        )?,
+        tags,
+    })
+}
+
+/// Keep `deleted` as the delete `undo_last` reverses, once it has been
+/// committed. A delete that removed nothing leaves the previous one in place.
+pub(crate) fn remember(deleted: DeletedSecrets) {
+    if deleted.secrets.is_empty() {
+        return;
+    }
+    *LAST_DELETE.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(deleted);
+}
+
+/// Drop the remembered delete, e.g. because its values are encrypted under
+/// a key the vault no longer uses
+pub(crate) fn forget() {
+    *LAST_DELETE.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = None;
+}
+
+/// Put back the secrets removed by the most recent `delete_secret`,
+/// `delete_secrets`, `delete_by_prefix`, `delete_by_tag` or `clear_vault`,
+/// or the original of the most recent `expand_secret`, in this session,
+/// with their ids, timestamps, history, aliases and tags. Values were kept
+/// encrypted in the meantime. Fails if there is nothing to undo, or with
+/// `KeyExists` if one of the keys has been reused since, in which case
+/// nothing is restored. Changing the master password or a namespace
+/// password, or switching databases, discards what could be undone.
+#[napi]
+pub fn undo_last() -> napi::Result<(), ErrorCode> {
+    with_conn(|conn| {
+        // Taken inside the connection lock, the order the deletes take them in
+        let mut last = LAST_DELETE.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let Some(deleted) = last.as_ref() else {
+            return Err(VaultError::InvalidArgument("nothing to undo".to_string()));
+        };
+
+        let tx = conn.transaction()?;
+        for (_, namespace, key) in &deleted.secrets {
+            let taken = tx
+                .prepare("SELECT 1 FROM secrets WHERE namespace = ?1 AND key = ?2")?
+                .exists(params![namespace, key])?;
+            if taken {
+                return Err(VaultError::KeyExists(key.clone()));
+            }
+        }
+
+        deleted.rows.restore(&tx, "secrets")?;
+        deleted.history.restore(&tx, "secret_history")?;
+        deleted.aliases.restore(&tx, "secret_aliases")?;
+        for (id, tag) in &deleted.tags {
+            tx.execute("INSERT OR IGNORE INTO tags (name) VALUES (?1)", params![tag])?;
+            tx.execute(
+                "INSERT OR IGNORE INTO secret_tags (secret_id, tag_id) SELECT ?1, id FROM tags WHERE name = ?2",
+                params![id, tag],
+            )?;
+        }
+        for (id, ..) in &deleted.secrets {
+            audit_log::record_secret(&tx, "restore", *id)?;
+        }
+        tx.commit()?;
+
+        *last = None;
+        Ok(())
+    })?;
+
+    Ok(())
+}
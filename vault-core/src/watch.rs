@@ -0,0 +1,112 @@
+//! Notice changes other processes make to the vault, such as the CLI
+//! writing while the app is open, so the UI can refresh.
+//!
+//! The database and its write-ahead log are polled for a new modification
+//! time or length rather than watched with `notify`, which isn't a
+//! dependency. Polling only stats the two files; the connection is locked
+//! only once they have changed, to check SQLite's `data_version`.
+
+use crate::error::{ErrorCode, VaultError, VaultResult};
+use crate::{get_db_path, get_meta, is_in_memory, with_conn};
+use napi::threadsafe_function::{ErrorStrategy, ThreadsafeFunction, ThreadsafeFunctionCallMode};
+use napi_derive::napi;
+use rusqlite::Connection;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+/// How often the database files are checked for changes
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Stop flag of the running watcher, if any
+static WATCHER: Mutex<Option<Arc<AtomicBool>>> = Mutex::new(None);
+
+/// Modification time and length of a file, or `None` if it doesn't exist
+type FileStamp = Option<(SystemTime, u64)>;
+
+/// Stamps of the database file and its write-ahead log, where writes land
+/// until they are checkpointed
+fn stamps(path: &Path) -> [FileStamp; 2] {
+    let stamp = |path: &Path| {
+        let metadata = std::fs::metadata(path).ok()?;
+        Some((metadata.modified().ok()?, metadata.len()))
+    };
+    let mut wal = path.as_os_str().to_owned();
+    wal.push("-wal");
+    [stamp(path), stamp(&PathBuf::from(wal))]
+}
+
+/// SQLite's counter of commits made by connections other than this one,
+/// so changes this process makes itself don't count
+fn data_version(conn: &Connection) -> VaultResult<i64> {
+    Ok(conn.query_row("PRAGMA data_version", [], |row| row.get(0))?)
+}
+
+/// The revision `vault_revision` returns
+fn revision(conn: &Connection) -> i64 {
+    get_meta(conn, "revision").and_then(|revision| revision.parse().ok()).unwrap_or(0)
+}
+
+/// Call `callback` with the new `vault_revision` whenever another process
+/// changes what the lists show. The database file is checked every 250ms
+/// and a burst of writes is reported once, after it settles; changes this
+/// process makes aren't reported. Replaces any earlier watcher. The
+/// callback keeps Node running until `unwatch_changes` is called.
+#[napi(ts_args_type = "callback: (revision: number) => void")]
+pub fn watch_changes(callback: ThreadsafeFunction<i64, ErrorStrategy::Fatal>) -> napi::Result<(), ErrorCode> {
+    if is_in_memory() {
+        return Err(VaultError::InvalidArgument("an in-memory vault has no file to watch".to_string()).into());
+    }
+    let path = get_db_path();
+    let (mut last_version, mut last_revision) = with_conn(|conn| Ok((data_version(conn)?, revision(conn))))?;
+
+    let stop = Arc::new(AtomicBool::new(false));
+    let previous = WATCHER.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).replace(stop.clone());
+    if let Some(previous) = previous {
+        previous.store(true, Ordering::Relaxed);
+    }
+
+    thread::spawn(move || {
+        let mut seen = stamps(&path);
+        while !stop.load(Ordering::Relaxed) {
+            thread::sleep(POLL_INTERVAL);
+            let current = stamps(&path);
+            if current == seen {
+                continue;
+            }
+            seen = current;
+            // Wait for the writer to finish before looking
+            loop {
+                thread::sleep(POLL_INTERVAL);
+                let current = stamps(&path);
+                if current == seen {
+                    break;
+                }
+                seen = current;
+            }
+
+            let changed = with_conn(|conn| {
+                let version = data_version(conn)?;
+                let revision = revision(conn);
+                let changed = version != last_version && revision != last_revision;
+                (last_version, last_revision) = (version, revision);
+                Ok(changed.then_some(revision))
+            });
+            if let (Ok(Some(revision)), false) = (changed, stop.load(Ordering::Relaxed)) {
+                callback.call(revision, ThreadsafeFunctionCallMode::NonBlocking);
+            }
+        }
+    });
+
+    Ok(())
+}
+
+/// Stop the watcher `watch_changes` started, if there is one
+#[napi]
+pub fn unwatch_changes() {
+    if let Some(stop) = WATCHER.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).take() {
+        stop.store(true, Ordering::Relaxed);
+    }
+}